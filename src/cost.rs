@@ -1,4 +1,5 @@
-use Cost;
+use {Cost, Model};
+use num_traits::Float;
 
 /// Pass an instance of this type to a training algorithm to optimize for C=Error^2
 ///
@@ -12,7 +13,32 @@ impl Cost<f64> for LeastSquares {
     }
 
     fn cost(&self, prediction: f64, truth: f64) -> f64 {
-        (prediction - truth).powi(2)
+        Float::powi(prediction - truth, 2)
+    }
+}
+
+/// Least squares of the error measured in units of `scale`, i.e. C=(Error/scale)^2
+///
+/// The error is divided by `scale` before it is multiplied with anything, so gradients stay
+/// finite for targets and features too large for `LeastSquares`, whose gradient
+/// `2 * error * feature` may overflow. Choose `scale` close to the magnitude of the targets.
+///
+/// The tradeoff: minimizing `ScaledLeastSquares` yields the same optimum as `LeastSquares`, but
+/// every gradient is `scale^2` times smaller, which the learning rate must make up for. Costs are
+/// not comparable to those of `LeastSquares` either.
+pub struct ScaledLeastSquares {
+    /// Expected magnitude of the error
+    pub scale: f64,
+}
+
+impl Cost<f64> for ScaledLeastSquares {
+    fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+        let error = (prediction - truth) / self.scale;
+        2.0 * error / self.scale
+    }
+
+    fn cost(&self, prediction: f64, truth: f64) -> f64 {
+        Float::powi((prediction - truth) / self.scale, 2)
     }
 }
 
@@ -34,7 +60,52 @@ impl Cost<f64> for LeastAbsoluteDeviation {
         }
     }
     fn cost(&self, prediction: f64, truth: f64) -> f64 {
-        (prediction - truth).abs()
+        Float::abs(prediction - truth)
+    }
+}
+
+/// Deviance of a Tweedie distribution with variance `mean^power`
+///
+/// Interpolates between well known distributions: `power = 0` is the normal distribution (same
+/// as `LeastSquares`), `power = 1` Poisson, `power = 2` Gamma and `power = 3` inverse Gaussian.
+/// Values in between `1` and `2` model a compound Poisson-Gamma distribution, e.g. insurance
+/// claims which are often zero and otherwise positive. No Tweedie distribution exists for powers
+/// in between `0` and `1`, so `power` must be `0` or at least `1`.
+///
+/// For `power >= 1` predictions must be positive, truths must be non negative (positive for
+/// `power >= 2`). Use a model with a log link, e.g. `model::Exponential`, to keep predictions
+/// positive.
+pub struct Tweedie {
+    /// Power of the mean which determines the variance
+    pub power: f64,
+}
+
+impl Cost<f64> for Tweedie {
+    fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+        2.0 * Float::powf(prediction, -self.power) * (prediction - truth)
+    }
+
+    fn cost(&self, prediction: f64, truth: f64) -> f64 {
+        let p = self.power;
+        if p == 0.0 {
+            Float::powi(prediction - truth, 2)
+        } else if p == 1.0 {
+            // y ln(y/mu) is zero for y = 0
+            let log_term = if truth > 0.0 {
+                truth * Float::ln(truth / prediction)
+            } else {
+                0.0
+            };
+            2.0 * (log_term - (truth - prediction))
+        } else if p == 2.0 {
+            2.0 * (Float::ln(prediction / truth) + truth / prediction - 1.0)
+        } else {
+            let y = Float::max(truth, 0.0);
+            2.0 *
+            (Float::powf(y, 2.0 - p) / ((1.0 - p) * (2.0 - p)) -
+             truth * Float::powf(prediction, 1.0 - p) / (1.0 - p) +
+             Float::powf(prediction, 2.0 - p) / (2.0 - p))
+        }
     }
 }
 
@@ -46,6 +117,9 @@ impl Cost<f64> for LeastAbsoluteDeviation {
 /// yet this cost function has shown itself to converge quicker
 /// for some problems.
 ///
+/// The cost is the log-loss. The prediction is clamped into `[1e-15, 1 - 1e-15]` before taking
+/// the logarithm, so confidently wrong predictions yield a large but finite cost.
+///
 /// #Examples
 ///
 /// ```
@@ -75,12 +149,19 @@ impl Cost<f64> for LeastAbsoluteDeviation {
 /// ```
 pub struct MaxLikelihood;
 
+// Keeps the logarithm of predictions finite
+fn clamp_probability(prediction: f64) -> f64 {
+    const EPSILON: f64 = 1e-15;
+    Float::min(Float::max(prediction, EPSILON), 1.0 - EPSILON)
+}
+
 impl Cost<f64> for MaxLikelihood {
     fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
         ((1.0 - truth) / (1.0 - prediction) - truth / prediction)
     }
     fn cost(&self, prediction: f64, truth: f64) -> f64 {
-        -truth * prediction.ln() - (1.0 - truth) * (1.0 - prediction).ln()
+        let p = clamp_probability(prediction);
+        -truth * Float::ln(p) - (1.0 - truth) * Float::ln(1.0 - p)
     }
 }
 
@@ -89,7 +170,148 @@ impl Cost<bool> for MaxLikelihood {
         1. / if truth { -prediction } else { 1.0 - prediction }
     }
     fn cost(&self, prediction: f64, truth: bool) -> f64 {
-        -(if truth { prediction } else { 1.0 - prediction }).ln()
+        let p = clamp_probability(prediction);
+        -Float::ln(if truth { p } else { 1.0 - p })
+    }
+}
+
+/// Cross entropy of predicted class probabilities and the index of the true class
+///
+/// Use with `model::Softmax`. Its predictions are probability vectors, so this does not
+/// implement `Cost`, which is defined for single number predictions. The cost is the negative
+/// logarithm of the probability of the true class.
+#[cfg(feature="std")]
+pub struct CategoricalCrossEntropy;
+
+#[cfg(feature="std")]
+impl CategoricalCrossEntropy {
+    /// Value of the cost function
+    pub fn cost(&self, probabilities: &[f64], class: usize) -> f64 {
+        -Float::ln(clamp_probability(probabilities[class]))
+    }
+
+    /// Gradient of the cost by the logits the `probabilities` are the softmax of
+    ///
+    /// This is `probabilities` minus the one-hot encoding of `class`.
+    pub fn logit_gradient(&self, probabilities: &[f64], class: usize) -> Vec<f64> {
+        probabilities.iter()
+            .enumerate()
+            .map(|(k, &p)| if k == class { p - 1.0 } else { p })
+            .collect()
+    }
+}
+
+/// Weights the cost function `C` depending on the class of the truth
+///
+/// Use this for imbalanced classification problems. Otherwise the majority class may dominate
+/// the training so much, that the model always predicts it. Events of the positive class are
+/// weighted with `positive`, events of the negative class with `negative`. Probabilities as
+/// truth are weighted with the respectively interpolated weight.
+pub struct ClassWeighted<C> {
+    /// Cost function to weight
+    pub cost: C,
+    /// Weight of events whose truth is `true` or `1`
+    pub positive: f64,
+    /// Weight of events whose truth is `false` or `0`
+    pub negative: f64,
+}
+
+impl<C> Cost<bool> for ClassWeighted<C>
+    where C: Cost<bool>
+{
+    fn outer_derivative(&self, prediction: f64, truth: bool) -> f64 {
+        self.weight(truth) * self.cost.outer_derivative(prediction, truth)
+    }
+    fn gradient_full<M>(&self,
+                        prediction: f64,
+                        truth: bool,
+                        model: &M,
+                        features: &M::Input,
+                        coefficent: usize)
+                        -> f64
+        where M: Model
+    {
+        self.weight(truth) * self.cost.gradient_full(prediction, truth, model, features, coefficent)
+    }
+    fn cost(&self, prediction: f64, truth: bool) -> f64 {
+        self.weight(truth) * self.cost.cost(prediction, truth)
+    }
+}
+
+impl<C> Cost<f64> for ClassWeighted<C>
+    where C: Cost<f64>
+{
+    fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+        self.weight_of_probability(truth) * self.cost.outer_derivative(prediction, truth)
+    }
+    fn gradient_full<M>(&self,
+                        prediction: f64,
+                        truth: f64,
+                        model: &M,
+                        features: &M::Input,
+                        coefficent: usize)
+                        -> f64
+        where M: Model
+    {
+        self.weight_of_probability(truth) *
+        self.cost.gradient_full(prediction, truth, model, features, coefficent)
+    }
+    fn cost(&self, prediction: f64, truth: f64) -> f64 {
+        self.weight_of_probability(truth) * self.cost.cost(prediction, truth)
+    }
+}
+
+impl<C> ClassWeighted<C> {
+    fn weight(&self, truth: bool) -> f64 {
+        if truth { self.positive } else { self.negative }
+    }
+
+    fn weight_of_probability(&self, truth: f64) -> f64 {
+        truth * self.positive + (1.0 - truth) * self.negative
+    }
+}
+
+/// Linear combination of two costs for the same truth, i.e. C=a*C1 + b*C2
+///
+/// Use this to train for multiple objectives at once, e.g. blending `LeastSquares` with
+/// `LeastAbsoluteDeviation` for a fit less sensitive to outliers. Gradients are combined with
+/// the same weights as the costs. Nest `WeightedSum`s to combine more than two costs.
+pub struct WeightedSum<A, B> {
+    /// First cost function
+    pub first: A,
+    /// Second cost function
+    pub second: B,
+    /// Weight of `first`
+    pub first_weight: f64,
+    /// Weight of `second`
+    pub second_weight: f64,
+}
+
+impl<A, B, Truth> Cost<Truth> for WeightedSum<A, B>
+    where A: Cost<Truth>,
+          B: Cost<Truth>,
+          Truth: Copy
+{
+    fn outer_derivative(&self, prediction: f64, truth: Truth) -> f64 {
+        self.first_weight * self.first.outer_derivative(prediction, truth) +
+        self.second_weight * self.second.outer_derivative(prediction, truth)
+    }
+    fn gradient_full<M>(&self,
+                        prediction: f64,
+                        truth: Truth,
+                        model: &M,
+                        features: &M::Input,
+                        coefficent: usize)
+                        -> f64
+        where M: Model
+    {
+        let first = self.first.gradient_full(prediction, truth, model, features, coefficent);
+        let second = self.second.gradient_full(prediction, truth, model, features, coefficent);
+        self.first_weight * first + self.second_weight * second
+    }
+    fn cost(&self, prediction: f64, truth: Truth) -> f64 {
+        self.first_weight * self.first.cost(prediction, truth) +
+        self.second_weight * self.second.cost(prediction, truth)
     }
 }
 
@@ -97,7 +319,8 @@ impl Cost<bool> for MaxLikelihood {
 mod test {
 
     use super::super::Cost;
-    use super::{LeastSquares, LeastAbsoluteDeviation, MaxLikelihood};
+    use super::{LeastSquares, LeastAbsoluteDeviation, MaxLikelihood, ClassWeighted,
+                ScaledLeastSquares, WeightedSum, Tweedie};
 
     // Approximates the derivation of the cost function
     fn approx_derivate<T: Copy>(cost: &Cost<T>, prediction: f64, truth: T) -> f64 {
@@ -125,6 +348,13 @@ mod test {
         assert!(check_derivate(&cost, 10.0, 12.0) < 0.001);
     }
 
+    #[test]
+    fn scaled_least_squares_derivation() {
+
+        let cost = ScaledLeastSquares { scale: 4.0 };
+        assert!(check_derivate(&cost, 10.0, 12.0) < 0.001);
+    }
+
     #[test]
     fn least_absolute_derivation() {
 
@@ -147,4 +377,91 @@ mod test {
         assert_eq!(cost.outer_derivative(0.8, true),
                    cost.outer_derivative(0.8, 1.0));
     }
+
+    #[test]
+    fn class_weighted_derivation() {
+
+        let cost = ClassWeighted {
+            cost: MaxLikelihood {},
+            positive: 4.0,
+            negative: 0.5,
+        };
+        assert!(check_derivate(&cost, 0.2, false) < 0.001);
+        assert!(check_derivate(&cost, 0.8, true) < 0.001);
+        assert!(check_derivate(&cost, 0.2, 0.0) < 0.001);
+        assert_eq!(4.0 * MaxLikelihood {}.outer_derivative(0.8, true),
+                   cost.outer_derivative(0.8, true));
+    }
+
+    #[test]
+    fn tweedie_derivation() {
+
+        for &power in &[0.0, 1.0, 1.5, 2.0, 3.0] {
+            let cost = Tweedie { power: power };
+            assert!(check_derivate(&cost, 2.5, 4.0) < 0.001);
+            assert!(check_derivate(&cost, 4.0, 0.5) < 0.001);
+        }
+
+        // Poisson deviance 2 * (y ln(y/mu) - (y - mu)) derived by mu
+        let poisson = Tweedie { power: 1.0 };
+        assert!((poisson.outer_derivative(2.5, 4.0) - 2.0 * (1.0 - 4.0 / 2.5)).abs() < 1e-12);
+        assert_eq!(2.0, poisson.outer_derivative(2.5, 0.0));
+        assert_eq!(LeastSquares {}.outer_derivative(2.5, 4.0),
+                   Tweedie { power: 0.0 }.outer_derivative(2.5, 4.0));
+    }
+
+    #[test]
+    fn weighted_sum_derivation() {
+
+        use super::super::Model;
+        use model::Linear;
+
+        let cost = WeightedSum {
+            first: LeastSquares {},
+            second: LeastAbsoluteDeviation {},
+            first_weight: 0.7,
+            second_weight: 0.3,
+        };
+        assert!(check_derivate(&cost, 10.0, 12.0) < 0.001);
+
+        let model = Linear {
+            m: [1.0, -2.0],
+            c: 0.5,
+        };
+        let features = [3.0, 1.0];
+        let prediction = model.predict(&features);
+        for ci in 0..model.num_coefficents() {
+            let squares = LeastSquares {}.gradient_full(prediction, 4.0, &model, &features, ci);
+            let absolute = LeastAbsoluteDeviation {}
+                .gradient_full(prediction, 4.0, &model, &features, ci);
+            assert_eq!(0.7 * squares + 0.3 * absolute,
+                       cost.gradient_full(prediction, 4.0, &model, &features, ci));
+        }
+    }
+
+    #[cfg(feature="std")]
+    #[test]
+    fn categorical_cross_entropy_derivation() {
+
+        use super::CategoricalCrossEntropy;
+
+        let cost = CategoricalCrossEntropy {};
+        let softmax = |logits: &[f64]| -> Vec<f64> {
+            let sum: f64 = logits.iter().map(|l| l.exp()).sum();
+            logits.iter().map(|l| l.exp() / sum).collect()
+        };
+        let logits = [0.5, -1.0, 2.0];
+        let gradient = cost.logit_gradient(&softmax(&logits), 1);
+
+        let epsilon = 0.00001;
+        for k in 0..logits.len() {
+            let mut plus = logits;
+            plus[k] += epsilon;
+            let mut minus = logits;
+            minus[k] -= epsilon;
+            let approx = (cost.cost(&softmax(&plus), 1) - cost.cost(&softmax(&minus), 1)) /
+                         (2.0 * epsilon);
+            assert!((gradient[k] - approx).abs() < 0.001);
+        }
+    }
 }
@@ -0,0 +1,335 @@
+use Model;
+use coefficents;
+
+/// Lazily yields the prediction of `model` for each event in `history`, paired with its truth
+///
+/// Metrics and calibration plots can consume these pairs, without repeating the prediction
+/// loop.
+pub fn predictions<'a, M, H, Truth>(model: &'a M,
+                                    history: H)
+                                    -> impl Iterator<Item = (f64, Truth)> + 'a
+    where M: Model,
+          H: IntoIterator<Item = (M::Input, Truth)>,
+          H::IntoIter: 'a
+{
+    history.into_iter().map(move |(input, truth)| (model.predict(&input), truth))
+}
+
+// Predictions of `model` for each event in `history`, paired with the truth
+fn prediction_truth_pairs<M, H>(model: &M, history: H) -> Vec<(f64, f64)>
+    where M: Model,
+          H: IntoIterator<Item = (M::Input, f64)>
+{
+    predictions(model, history).collect()
+}
+
+fn mean<I>(values: I) -> f64
+    where I: Iterator<Item = f64>
+{
+    let (sum, n) = values.fold((0.0, 0.0), |(sum, n), v| (sum + v, n + 1.0));
+    sum / n
+}
+
+fn variance<I>(values: I) -> f64
+    where I: Iterator<Item = f64> + Clone
+{
+    let m = mean(values.clone());
+    mean(values.map(|v| (v - m).powi(2)))
+}
+
+/// Residual `prediction - truth` of `model` for each event in `history`
+///
+/// Useful to inspect the distribution of the errors, e.g. for heteroscedasticity.
+pub fn residuals<M, H>(model: &M, history: H) -> Vec<f64>
+    where M: Model,
+          H: IntoIterator<Item = (M::Input, f64)>
+{
+    predictions(model, history).map(|(p, t)| p - t).collect()
+}
+
+/// Coefficient of determination of `model` over `history`
+///
+/// Compares the squared error of `model` with the squared error of the baseline, which
+/// always predicts the mean truth of `history`. A perfect model yields `1`, a model no better
+/// than the baseline `0`.
+pub fn r_squared<M, H>(model: &M, history: H) -> f64
+    where M: Model,
+          H: IntoIterator<Item = (M::Input, f64)>
+{
+    let pairs = prediction_truth_pairs(model, history);
+    let mean_truth = mean(pairs.iter().map(|&(_, t)| t));
+    let residual: f64 = pairs.iter().map(|&(p, t)| (t - p).powi(2)).sum();
+    let total: f64 = pairs.iter().map(|&(_, t)| (t - mean_truth).powi(2)).sum();
+    1.0 - residual / total
+}
+
+/// Fraction of the variance of the truth in `history` explained by `model`
+///
+/// In contrast to `r_squared` a constant bias of the predictions is not penalized.
+pub fn explained_variance<M, H>(model: &M, history: H) -> f64
+    where M: Model,
+          H: IntoIterator<Item = (M::Input, f64)>
+{
+    let pairs = prediction_truth_pairs(model, history);
+    let residual = variance(pairs.iter().map(|&(p, t)| t - p));
+    let total = variance(pairs.iter().map(|&(_, t)| t));
+    1.0 - residual / total
+}
+
+/// Mean squared error, mean absolute error and coefficient of determination over a stream of
+/// predictions
+///
+/// Pairs of prediction and truth are added one at a time with `add`, without storing them. All
+/// quantities are running means, and the variance of the truth is tracked with Welford's
+/// algorithm, so precision does not degrade over many updates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RunningMetrics {
+    /// Number of pairs added
+    pub count: usize,
+    /// Mean of `(prediction - truth)^2`
+    pub mean_squared_error: f64,
+    /// Mean of `|prediction - truth|`
+    pub mean_absolute_error: f64,
+    /// Mean of the truths
+    pub mean_truth: f64,
+    /// Sum of the squared deviations of the truths from `mean_truth`
+    pub truth_deviations: f64,
+}
+
+impl RunningMetrics {
+    /// Creates metrics without any pairs
+    pub fn new() -> RunningMetrics {
+        RunningMetrics::default()
+    }
+
+    /// Adds a single pair of `prediction` and `truth`
+    pub fn add(&mut self, prediction: f64, truth: f64) {
+        self.count += 1;
+        let n = self.count as f64;
+        let error = prediction - truth;
+        self.mean_squared_error += (error * error - self.mean_squared_error) / n;
+        self.mean_absolute_error += (error.abs() - self.mean_absolute_error) / n;
+        let delta = truth - self.mean_truth;
+        self.mean_truth += delta / n;
+        self.truth_deviations += delta * (truth - self.mean_truth);
+    }
+
+    /// Coefficient of determination of the pairs added so far, see `r_squared`
+    pub fn r_squared(&self) -> f64 {
+        1.0 - self.mean_squared_error * self.count as f64 / self.truth_deviations
+    }
+}
+
+/// Number of coefficents of `model` with a magnitude above `threshold`, and the total number
+///
+/// Quantifies how much a regularization, e.g. `teacher::TruncatedGradient`, sparsified `model`.
+pub fn sparsity<M>(model: &M, threshold: f64) -> (usize, usize)
+    where M: Model
+{
+    let coefficents = coefficents(model);
+    let nonzero = coefficents.iter().filter(|c| c.abs() > threshold).count();
+    (nonzero, coefficents.len())
+}
+
+/// Counts of correctly and wrongly classified events of a binary classifier
+///
+/// A prediction of at least `0.5` classifies an event as positive. A truth of `1` marks a
+/// positive event, `0` a negative one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConfusionMatrix {
+    /// Positive events classified as positive
+    pub true_positives: usize,
+    /// Negative events classified as positive
+    pub false_positives: usize,
+    /// Negative events classified as negative
+    pub true_negatives: usize,
+    /// Positive events classified as negative
+    pub false_negatives: usize,
+}
+
+impl ConfusionMatrix {
+    /// Counts the pairs of prediction and truth in `predictions`, e.g. the output of
+    /// `eval::predictions`
+    pub fn new<I>(predictions: I) -> ConfusionMatrix
+        where I: IntoIterator<Item = (f64, f64)>
+    {
+        let mut matrix = ConfusionMatrix::default();
+        for (prediction, truth) in predictions {
+            match (prediction >= 0.5, truth >= 0.5) {
+                (true, true) => matrix.true_positives += 1,
+                (true, false) => matrix.false_positives += 1,
+                (false, false) => matrix.true_negatives += 1,
+                (false, true) => matrix.false_negatives += 1,
+            }
+        }
+        matrix
+    }
+
+    /// Fraction of the events classified as positive, which are positive
+    pub fn precision(&self) -> f64 {
+        self.true_positives as f64 / (self.true_positives + self.false_positives) as f64
+    }
+
+    /// Fraction of the positive events, which are classified as positive (sensitivity)
+    pub fn recall(&self) -> f64 {
+        self.true_positives as f64 / (self.true_positives + self.false_negatives) as f64
+    }
+
+    /// Fraction of the negative events, which are classified as negative
+    pub fn specificity(&self) -> f64 {
+        self.true_negatives as f64 / (self.true_negatives + self.false_positives) as f64
+    }
+
+    /// Weighted harmonic mean of precision and recall, with recall `beta` times as important
+    ///
+    /// `0` if no event is classified as positive correctly.
+    pub fn fbeta_score(&self, beta: f64) -> f64 {
+        if self.true_positives == 0 {
+            return 0.0;
+        }
+        let (precision, recall) = (self.precision(), self.recall());
+        (1.0 + beta * beta) * precision * recall / (beta * beta * precision + recall)
+    }
+
+    /// Mean of recall and specificity
+    pub fn balanced_accuracy(&self) -> f64 {
+        (self.recall() + self.specificity()) / 2.0
+    }
+}
+
+/// F-beta score of the pairs of prediction and truth in `predictions`, see `ConfusionMatrix`
+///
+/// Values of `beta` larger than `1` emphasize recall, smaller ones precision.
+pub fn fbeta_score<I>(predictions: I, beta: f64) -> f64
+    where I: IntoIterator<Item = (f64, f64)>
+{
+    ConfusionMatrix::new(predictions).fbeta_score(beta)
+}
+
+/// Harmonic mean of precision and recall, the F-beta score for `beta = 1`
+pub fn f1_score<I>(predictions: I) -> f64
+    where I: IntoIterator<Item = (f64, f64)>
+{
+    let matrix = ConfusionMatrix::new(predictions);
+    if matrix.true_positives == 0 {
+        return 0.0;
+    }
+    let (precision, recall) = (matrix.precision(), matrix.recall());
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// Mean of the recall of the positive and the negative class
+///
+/// In contrast to the accuracy, always predicting the majority class of imbalanced data yields
+/// only `0.5`.
+pub fn balanced_accuracy<I>(predictions: I) -> f64
+    where I: IntoIterator<Item = (f64, f64)>
+{
+    ConfusionMatrix::new(predictions).balanced_accuracy()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{r_squared, explained_variance, residuals, sparsity, predictions, f1_score,
+                fbeta_score, balanced_accuracy, ConfusionMatrix, RunningMetrics};
+    use model::{Constant, Linear};
+
+    #[test]
+    fn r_squared_baseline() {
+
+        let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0), (3.0, 9.0)];
+
+        let perfect = Linear { m: 2.0, c: 3.0 };
+        let history_perfect: Vec<_> = history.iter().map(|&(x, _)| (x, 2.0 * x + 3.0)).collect();
+        assert_eq!(1.0, r_squared(&perfect, history_perfect.iter().cloned()));
+
+        let mean = Constant::new(5.25);
+        assert_eq!(0.0, r_squared(&mean, history.iter().cloned()));
+        assert_eq!(0.0, explained_variance(&mean, history.iter().cloned()));
+    }
+
+    #[test]
+    fn explained_variance_ignores_bias() {
+
+        let history = [(0.0, 3.0), (1.0, 5.0), (2.0, 7.0)];
+        let biased = Linear { m: 2.0, c: 4.0 };
+
+        assert_eq!(1.0, explained_variance(&biased, history.iter().cloned()));
+        assert!(r_squared(&biased, history.iter().cloned()) < 1.0);
+    }
+
+    #[test]
+    fn residuals_of_fit_model() {
+
+        let history = [(0.0, 3.0), (1.0, 5.0), (2.0, 6.9)];
+        let model = Linear { m: 1.95, c: 3.03 };
+
+        let residuals = residuals(&model, history.iter().cloned());
+
+        assert_eq!(3, residuals.len());
+        assert!(residuals.iter().all(|r| r.abs() < 0.1));
+    }
+
+    #[test]
+    fn running_metrics_match_batch() {
+
+        let history: Vec<_> = (0..1000)
+            .map(|i| {
+                let x = i as f64 / 100.0;
+                (x, 1e6 + 2.0 * x + (x * 7.0).sin())
+            })
+            .collect();
+        let model = Linear { m: 2.1, c: 1e6 };
+
+        let mut metrics = RunningMetrics::new();
+        for (prediction, truth) in predictions(&model, history.iter().cloned()) {
+            metrics.add(prediction, truth);
+        }
+        let residuals = residuals(&model, history.iter().cloned());
+        let n = residuals.len() as f64;
+
+        assert_eq!(1000, metrics.count);
+        assert!((metrics.r_squared() - r_squared(&model, history.iter().cloned())).abs() < 1e-9);
+        let mse = residuals.iter().map(|r| r * r).sum::<f64>() / n;
+        assert!((metrics.mean_squared_error - mse).abs() < 1e-9);
+        let mae = residuals.iter().map(|r| r.abs()).sum::<f64>() / n;
+        assert!((metrics.mean_absolute_error - mae).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sparsity_of_truncated_model() {
+
+        let model = Linear {
+            m: vec![1.5, 1e-9, 0.0, -0.7],
+            c: 2.0,
+        };
+
+        assert_eq!((3, 5), sparsity(&model, 1e-6));
+        assert_eq!((0, 5), sparsity(&model, 10.0));
+    }
+
+    #[test]
+    fn fbeta_and_balanced_accuracy() {
+
+        let pairs = [(0.9, 1.0), (0.8, 1.0), (0.3, 1.0), (0.6, 0.0), (0.1, 0.0), (0.2, 0.0),
+                     (0.7, 1.0)];
+        let matrix = ConfusionMatrix::new(pairs.iter().cloned());
+        assert_eq!(3, matrix.true_positives);
+        assert_eq!(1, matrix.false_positives);
+        assert_eq!(2, matrix.true_negatives);
+        assert_eq!(1, matrix.false_negatives);
+
+        let f1 = f1_score(pairs.iter().cloned());
+        assert!((f1 - 0.75).abs() < 1e-12);
+        assert!((fbeta_score(pairs.iter().cloned(), 1.0) - f1).abs() < 1e-12);
+        // Precision equals recall, so every beta yields the same score
+        assert!((fbeta_score(pairs.iter().cloned(), 2.0) - f1).abs() < 1e-12);
+
+        // Always predicting the negative majority class is accurate for 90% of the events
+        let majority = Constant::new(0.0);
+        let history: Vec<((), f64)> =
+            (0..100).map(|i| ((), if i < 10 { 1.0 } else { 0.0 })).collect();
+        assert_eq!(0.5, balanced_accuracy(predictions(&majority, history.iter().cloned())));
+    }
+}
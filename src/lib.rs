@@ -18,11 +18,25 @@
 #![warn(missing_docs)]
 #![cfg_attr(feature="clippy", feature(plugin))]
 #![cfg_attr(feature="clippy", plugin(clippy))]
+#![cfg_attr(not(feature="std"), no_std)]
 
+// Without `std` the float math (`sqrt`, `exp`, ...) is provided by `libm`
+#[cfg(all(not(feature="std"), not(feature="libm")))]
+compile_error!("vikos requires either the std or the libm feature");
+
+#[cfg(not(feature="std"))]
+extern crate core as std;
+#[cfg(feature="std")]
 extern crate rustc_serialize;
-extern crate num;
+extern crate num_traits;
+#[cfg(feature="rand")]
+extern crate rand;
+#[cfg(feature="rayon")]
+extern crate rayon;
 
 use std::iter::IntoIterator;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// A Model is a parameterized expert algorithm
 ///
@@ -43,8 +57,86 @@ pub trait Model: Clone {
 
     /// Mutable reference to the n-th `coefficent`
     fn coefficent(&mut self, coefficent: usize) -> &mut f64;
+
+    /// Checks whether `sample_input` fits the coefficent layout of this model
+    ///
+    /// Call this before training starts to catch configuration errors early,
+    /// rather than running into an out of bounds panic in `gradient`. The
+    /// default implementation accepts any input.
+    fn validate(&self, _sample_input: &Self::Input) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Object safe part of `Model`, which only predicts
+///
+/// `Model` requires `Clone`, so it can not be used as a trait object. Every `Model` implements
+/// `DynModel`, which allows to store models of different types with the same input in a single
+/// collection, e.g. `Vec<Box<dyn DynModel<[f64; 2]>>>`. Predictions are always `f64`.
+///
+/// Its method is named `predict_dyn`, so calling `predict` on a `Model` stays unambiguous with
+/// both traits in scope.
+pub trait DynModel<Input> {
+    /// Predicts a target for the inputs based on the internal coefficents
+    fn predict_dyn(&self, input: &Input) -> f64;
+}
+
+impl<M> DynModel<M::Input> for M
+    where M: Model
+{
+    fn predict_dyn(&self, input: &M::Input) -> f64 {
+        Model::predict(self, input)
+    }
+}
+
+/// Errors reported by vikos
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Dimension of an input does not match the dimension expected by the model
+    DimensionMismatch {
+        /// Dimension expected by the model
+        expected: usize,
+        /// Dimension of the input passed
+        actual: usize,
+    },
+    /// A checkpoint could not be decoded
+    InvalidCheckpoint,
+    /// Number of coefficents passed does not match the number required by the model
+    CoefficentCountMismatch {
+        /// Number of coefficents required by the model
+        expected: usize,
+        /// Number of coefficents passed
+        actual: usize,
+    },
+    /// A system of linear equations has no unique solution, e.g. because a feature is constant or
+    /// two features are collinear
+    SingularMatrix,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::DimensionMismatch { expected, actual } => {
+                write!(f,
+                       "input has dimension {}, but model expects dimension {}",
+                       actual,
+                       expected)
+            }
+            Error::InvalidCheckpoint => write!(f, "checkpoint could not be decoded"),
+            Error::CoefficentCountMismatch { expected, actual } => {
+                write!(f,
+                       "{} coefficents passed, but model requires {}",
+                       actual,
+                       expected)
+            }
+            Error::SingularMatrix => write!(f, "system of linear equations is singular"),
+        }
+    }
 }
 
+#[cfg(feature="std")]
+impl std::error::Error for Error {}
+
 /// Representing a cost function whose value is supposed be minimized by the
 /// training algorithm.
 ///
@@ -74,6 +166,25 @@ pub trait Cost<Truth> {
         self.outer_derivative(prediction, truth) * derivative_of_model
     }
 
+    /// Value of the gradient of the cost function by the `coefficent`-th coefficent of `model`
+    /// for the event `features`
+    ///
+    /// Training algorithms call this method. The default implementation calls `gradient` with
+    /// the derivative of `model`. Costs which depend on more than the prediction, e.g. on the
+    /// features or on several coefficents jointly, may overwrite it.
+    fn gradient_full<M>(&self,
+                        prediction: f64,
+                        truth: Truth,
+                        model: &M,
+                        features: &M::Input,
+                        coefficent: usize)
+                        -> f64
+        where M: Model,
+              Self: Sized
+    {
+        self.gradient(prediction, truth, model.gradient(coefficent, features))
+    }
+
     /// The outer derivative of the cost function with respect to the prediction.
     fn outer_derivative(&self, prediction: f64, truth: Truth) -> f64;
 
@@ -101,6 +212,49 @@ pub trait Training {
                              truth: Truth)
         where C: Cost<Truth>,
               Truth: Copy;
+
+    /// Teaches `model` every event in `events`
+    ///
+    /// The default implementation calls `teach_event` for each event. Trainings which are able
+    /// to perform a true batched update may overwrite this method.
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut Self::Model,
+                             events: &[(<Self::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        for &(ref features, truth) in events {
+            self.teach_event(cost, model, features, truth);
+        }
+    }
+
+    /// Learning rate the next event would be taught with, if this training uses one
+    ///
+    /// Trainings following a schedule, e.g. `training::GradientDescentAl`, report the current
+    /// value of the schedule. The default implementation returns `None`.
+    fn current_learning_rate(&self) -> Option<f64> {
+        None
+    }
+
+    /// Incrementally teaches `model` the events in `batch`
+    ///
+    /// Same as `teach_batch`. The `Training` keeps its state (e.g. the velocity of the
+    /// coefficents, or the number of learned events) between calls. For trainings teaching one
+    /// event at a time, i.e. those not overriding `teach_batch`, teaching successive batches
+    /// therefore results in the same model as teaching all of them at once. Batch trainings,
+    /// e.g. `training::Lbfgs`, take one step per call instead, so their result depends on how
+    /// the events are split. Use this to train on data which arrives over time, or does not fit
+    /// into memory.
+    fn partial_fit<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut Self::Model,
+                             batch: &[(<Self::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.teach_batch(cost, model, batch)
+    }
 }
 
 /// Factories for [Training](./trait.Training.html)
@@ -131,14 +285,366 @@ pub fn learn_history<M, C, T, H, Truth>(teacher: &T, cost: &C, model: &mut M, hi
     }
 }
 
+/// How `learn_history_with_missing` treats events without a truth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingPolicy {
+    /// Events without a truth are not taught
+    Skip,
+    /// Events without a truth are taught with the mean of all truths seen so far
+    ///
+    /// Events preceding the first known truth are skipped.
+    ImputeMean,
+}
+
+/// Teaches `model` all events in `history`, some of which may lack a truth
+///
+/// Events whose truth is `None` are handled according to `policy`, so missing labels never
+/// reach the cost function as e.g. `NaN`.
+pub fn learn_history_with_missing<M, C, T, H>(teacher: &T,
+                                              cost: &C,
+                                              model: &mut M,
+                                              history: H,
+                                              policy: MissingPolicy)
+    where M: Model,
+          C: Cost<f64>,
+          T: Teacher<M>,
+          H: IntoIterator<Item = (M::Input, Option<f64>)>
+{
+    let mut training = teacher.new_training(model);
+    let mut mean = 0.0;
+    let mut known = 0.0;
+    for (features, truth) in history {
+        let truth = match (truth, policy) {
+            (Some(truth), _) => {
+                known += 1.0;
+                mean += (truth - mean) / known;
+                truth
+            }
+            (None, MissingPolicy::ImputeMean) if known > 0.0 => mean,
+            (None, _) => continue,
+        };
+        training.teach_event(cost, model, &features, truth);
+    }
+}
+
+/// Trains a copy of `start` on `epochs` passes over `history` and returns it
+///
+/// Shorthand for cloning a model and calling `learn_history` with the events of `history`
+/// repeated `epochs` times. A single training is used for all epochs.
+///
+/// # Examples
+///
+/// ```
+/// use vikos::{fit, cost, model, teacher};
+///
+/// let history = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)];
+/// let start = model::Linear { m: 0.0, c: 0.0 };
+/// let teacher = teacher::GradientDescent { learning_rate: 0.05 };
+/// let model = fit(&teacher, &cost::LeastSquares {}, &start, &history, 1000);
+/// assert!((model.m - 2.0).abs() < 0.01);
+/// ```
+pub fn fit<M, C, T, Truth>(teacher: &T,
+                           cost: &C,
+                           start: &M,
+                           history: &[(M::Input, Truth)],
+                           epochs: usize)
+                           -> M
+    where M: Model,
+          M::Input: Clone,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          Truth: Copy
+{
+    let mut model = start.clone();
+    learn_history(teacher,
+                  cost,
+                  &mut model,
+                  history.iter().cycle().take(epochs * history.len()).cloned());
+    model
+}
+
+/// Teaches `model` every batch yielded by `batches`
+///
+/// Calls `Training::teach_batch` once for each batch. Batches are borrowed, so they can be read
+/// lazily from any backing store, e.g. a memory mapped file or a database, without collecting
+/// all of them first. The training state is kept across batches.
+pub fn teach_batched_stream<'a, M, C, T, I, Truth>(teacher: &T, cost: &C, model: &mut M, batches: I)
+    where M: Model,
+          M::Input: 'a,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          I: IntoIterator<Item = &'a [(M::Input, Truth)]>,
+          Truth: Copy + 'a
+{
+    let mut training = teacher.new_training(model);
+    for batch in batches {
+        training.teach_batch(cost, model, batch);
+    }
+}
+
+/// Adapts a history with integer truths, e.g. counts or class labels, into one with `f64` truths
+pub fn into_float_history<I, H>(history: H) -> std::iter::Map<H::IntoIter, fn((I, i32)) -> (I, f64)>
+    where H: IntoIterator<Item = (I, i32)>
+{
+    fn to_float<I>((input, truth): (I, i32)) -> (I, f64) {
+        (input, truth as f64)
+    }
+    history.into_iter().map(to_float::<I> as fn((I, i32)) -> (I, f64))
+}
+
+/// Why `learn_epochs` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The mean cost changed less than the tolerance during the last epoch
+    Converged,
+    /// The maximum number of epochs has been reached
+    MaxEpochs,
+    /// The cancellation flag passed to `learn_epochs_cancellable` has been set
+    Cancelled,
+}
+
+/// Summary of a call to `learn_epochs`
+#[derive(Debug, Clone)]
+pub struct TrainingReport {
+    /// Number of passes over the history
+    pub epochs_run: usize,
+    /// Mean cost over the history after the last epoch
+    pub final_cost: f64,
+    /// `true` if the training stopped because the cost converged
+    pub converged: bool,
+    /// Why the training stopped
+    pub stopped_reason: StopReason,
+    /// Number of events taught, i.e. `epochs_run` times the length of the history
+    pub events_learned: usize,
+    /// Wall-clock time of the training, including the evaluation of the cost after each epoch
+    #[cfg(feature="std")]
+    pub elapsed: std::time::Duration,
+    /// Learning rate at the start of each epoch, see `Training::current_learning_rate`
+    ///
+    /// One entry per epoch, not per step: Trainings stepping once per event, e.g. with a decaying
+    /// schedule, report only the rate of the first event of each epoch. For batch trainings,
+    /// which step once per epoch, this is the rate of every step. Empty if the training does not
+    /// report a learning rate.
+    #[cfg(feature="std")]
+    pub learning_rates: Vec<f64>,
+}
+
+#[cfg(feature="std")]
+impl TrainingReport {
+    /// Number of events taught per second of `elapsed`
+    pub fn events_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs() as f64 + self.elapsed.subsec_nanos() as f64 * 1e-9;
+        self.events_learned as f64 / seconds
+    }
+}
+
+/// Mean `cost` of the predictions of `model` over `history`
+pub fn mean_cost<M, C, Truth>(cost: &C, model: &M, history: &[(M::Input, Truth)]) -> f64
+    where M: Model,
+          C: Cost<Truth>,
+          Truth: Copy
+{
+    let sum = history.iter()
+        .fold(0.0, |sum, &(ref features, truth)| sum + cost.cost(model.predict(features), truth));
+    sum / history.len() as f64
+}
+
+/// Teaches `model` the events in `history` for at most `max_epochs` passes
+///
+/// Each epoch passes all of `history` to `Training::teach_batch`. The training stops early once
+/// the mean cost over `history` changes less than `tolerance` during an epoch.
+pub fn learn_epochs<M, C, T, Truth>(teacher: &T,
+                                    cost: &C,
+                                    model: &mut M,
+                                    history: &[(M::Input, Truth)],
+                                    max_epochs: usize,
+                                    tolerance: f64)
+                                    -> TrainingReport
+    where M: Model,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          Truth: Copy
+{
+    learn_epochs_cancellable(teacher, cost, model, history, max_epochs, tolerance, None)
+}
+
+/// Like `learn_epochs`, but also stops once `cancel` is set
+///
+/// The flag is checked between epochs, so e.g. a UI thread can stop a long running training.
+/// `model` keeps the coefficents learned so far.
+pub fn learn_epochs_cancellable<M, C, T, Truth>(teacher: &T,
+                                                cost: &C,
+                                                model: &mut M,
+                                                history: &[(M::Input, Truth)],
+                                                max_epochs: usize,
+                                                tolerance: f64,
+                                                cancel: Option<&AtomicBool>)
+                                                -> TrainingReport
+    where M: Model,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          Truth: Copy
+{
+    #[cfg(feature="std")]
+    let start = std::time::Instant::now();
+    let mut training = teacher.new_training(model);
+    let mut previous_cost = mean_cost(cost, model, history);
+    let mut epochs_run = 0;
+    let mut stopped_reason = StopReason::MaxEpochs;
+    #[cfg(feature="std")]
+    let mut learning_rates = Vec::new();
+
+    while epochs_run < max_epochs {
+        if cancel.map_or(false, |flag| flag.load(Ordering::SeqCst)) {
+            stopped_reason = StopReason::Cancelled;
+            break;
+        }
+        #[cfg(feature="std")]
+        learning_rates.extend(training.current_learning_rate());
+        training.teach_batch(cost, model, history);
+        epochs_run += 1;
+        let current_cost = mean_cost(cost, model, history);
+        let change = (previous_cost - current_cost).abs();
+        previous_cost = current_cost;
+        if change < tolerance {
+            stopped_reason = StopReason::Converged;
+            break;
+        }
+    }
+
+    TrainingReport {
+        epochs_run: epochs_run,
+        final_cost: previous_cost,
+        converged: stopped_reason == StopReason::Converged,
+        stopped_reason: stopped_reason,
+        events_learned: epochs_run * history.len(),
+        #[cfg(feature="std")]
+        elapsed: start.elapsed(),
+        #[cfg(feature="std")]
+        learning_rates: learning_rates,
+    }
+}
+
+/// Gradient of `cost` by each coefficent of `model` for a single event
+///
+/// The prediction is calculated only once for all coefficents.
+#[cfg(feature="std")]
+pub fn full_gradient<M, C, Truth>(cost: &C,
+                                  model: &M,
+                                  features: &M::Input,
+                                  truth: Truth)
+                                  -> Vec<f64>
+    where M: Model,
+          C: Cost<Truth>,
+          Truth: Copy
+{
+    gradient_values(cost, model, features, truth).collect()
+}
+
+// Yields the gradient of `cost` by each coefficent, like `full_gradient` but without allocating
+fn gradient_values<'a, M, C, Truth>(cost: &'a C,
+                                    model: &'a M,
+                                    features: &'a M::Input,
+                                    truth: Truth)
+                                    -> impl Iterator<Item = f64> + 'a
+    where M: Model,
+          C: Cost<Truth>,
+          Truth: Copy + 'a
+{
+    let prediction = model.predict(features);
+    (0..model.num_coefficents())
+        .map(move |ci| cost.gradient_full(prediction, truth, model, features, ci))
+}
+
+/// Plain gradient descent on an arbitrary differentiable `objective`
+///
+/// Starting at `x0`, takes `steps` steps of `learning_rate` times the negative `gradient`.
+/// Returns the visited position with the lowest value of `objective`. Useful for problems which
+/// do not fit the model, cost and supervised events framing.
+#[cfg(feature="std")]
+pub fn minimize<O, G>(objective: O, gradient: G, x0: &[f64], learning_rate: f64, steps: usize)
+                      -> Vec<f64>
+    where O: Fn(&[f64]) -> f64,
+          G: Fn(&[f64]) -> Vec<f64>
+{
+    let mut x = x0.to_vec();
+    let mut best = (objective(&x), x.clone());
+    for _ in 0..steps {
+        let g = gradient(&x);
+        for (x_i, g_i) in x.iter_mut().zip(g) {
+            *x_i -= learning_rate * g_i;
+        }
+        let value = objective(&x);
+        if value < best.0 {
+            best = (value, x.clone());
+        }
+    }
+    best.1
+}
+
+/// All coefficents of `model` in a flat vector, ordered by their index
+///
+/// Inverse of `model::FromCoefficents::from_coefficents`. Allows to optimize the coefficents
+/// with an external solver.
+#[cfg(feature="std")]
+pub fn coefficents<M>(model: &M) -> Vec<f64>
+    where M: Model
+{
+    coefficent_values(model).collect()
+}
+
+// Yields the coefficents of `model` ordered by their index, like `coefficents` but without
+// allocating. `Model::coefficent` requires mutable access, so this reads them from a copy.
+fn coefficent_values<M>(model: &M) -> impl Iterator<Item = f64>
+    where M: Model
+{
+    let mut model = model.clone();
+    (0..model.num_coefficents()).map(move |ci| *model.coefficent(ci))
+}
+
+/// Euclidean (L2) norm of the coefficents of `model`
+pub fn coefficent_norm<M>(model: &M) -> f64
+    where M: Model
+{
+    let sum = coefficent_values(model).fold(0.0, |sum, c| sum + c * c);
+    num_traits::Float::sqrt(sum)
+}
+
+/// Euclidean (L2) norm of the gradient of `cost` by the coefficents of `model` for a single event
+pub fn gradient_norm<M, C, Truth>(cost: &C, model: &M, features: &M::Input, truth: Truth) -> f64
+    where M: Model,
+          C: Cost<Truth>,
+          Truth: Copy
+{
+    let sum = gradient_values(cost, model, features, truth).fold(0.0, |sum, g| sum + g * g);
+    num_traits::Float::sqrt(sum)
+}
+
 /// Implementations of `Model` trait
 pub mod model;
 /// Implementations of `Cost` trait
 pub mod cost;
 /// Implementations of `Training` trait
+#[cfg(feature="std")]
 pub mod training;
 /// Implementations of `Teacher` trait
+#[cfg(feature="std")]
 pub mod teacher;
 /// Defines linear algebra traits used for some model parameters
 pub mod linear_algebra;
+/// Metrics to evaluate the predictions of a `Model`
+#[cfg(feature="std")]
+pub mod eval;
+/// Utilities to select models and their hyperparameters
+#[cfg(feature="std")]
+pub mod model_selection;
+/// Fuzzy comparisons of numbers and models, e.g. for tests
+pub mod testing;
+/// Preprocessing of features before training
+#[cfg(feature="std")]
+pub mod preprocess;
+/// Reproducible randomness for randomized trainings
+#[cfg(feature="rand")]
+pub mod random;
+#[cfg(feature="std")]
 pub mod tutorial;
@@ -1,6 +1,27 @@
-use num::{Num, Zero, One};
+use num_traits::{Num, Zero, One, Float};
+#[cfg(feature="std")]
 use rustc_serialize::{Encodable, Decodable};
 use std::fmt::Debug;
+#[cfg(feature="std")]
+use Error;
+
+/// Types which can be serialized along with the models using them
+///
+/// Requires `Encodable` and `Decodable` if the `std` feature is enabled and
+/// nothing otherwise.
+#[cfg(feature="std")]
+pub trait Serializable: Encodable + Decodable {}
+#[cfg(feature="std")]
+impl<T: Encodable + Decodable> Serializable for T {}
+
+/// Types which can be serialized along with the models using them
+///
+/// Requires `Encodable` and `Decodable` if the `std` feature is enabled and
+/// nothing otherwise.
+#[cfg(not(feature="std"))]
+pub trait Serializable {}
+#[cfg(not(feature="std"))]
+impl<T> Serializable for T {}
 
 /// Vector whose dimension is known at runtime
 ///
@@ -9,7 +30,7 @@ use std::fmt::Debug;
 /// along orthogonal base vectors
 pub trait Vector: Clone {
     /// Underlying scalar type of `Vector` type
-    type Scalar: Num + Zero + One + Copy + Encodable + Decodable + Default + Debug;
+    type Scalar: Num + Zero + One + Copy + Serializable + Default + Debug;
     /// Maximum allowed index for `at` and `mut_at`
     fn dimension(&self) -> usize;
     /// Length of projection along `i`-th base
@@ -48,6 +69,23 @@ impl Vector for f64 {
     }
 }
 
+#[cfg(feature="std")]
+impl Vector for Vec<f64> {
+    type Scalar = f64;
+
+    fn dimension(&self) -> usize {
+        self.len()
+    }
+
+    fn at(&self, index: usize) -> f64 {
+        self[index]
+    }
+
+    fn mut_at(&mut self, index: usize) -> &mut f64 {
+        &mut self[index]
+    }
+}
+
 macro_rules! vec_impl_for_array {
     ($v:expr) => {
         impl Vector for [f64; $v] {
@@ -100,6 +138,104 @@ vec_impl_for_array! { 30 }
 vec_impl_for_array! { 31 }
 vec_impl_for_array! { 32 }
 
+/// Running sum using Kahan compensated summation
+///
+/// Tracks the low order bits lost by each addition and adds them back to the next summand. The
+/// error of the sum stays bounded independently of the number of summands, whereas it grows
+/// with their number for naive summation. Used to accumulate the gradients of large batches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KahanSum<F> {
+    sum: F,
+    compensation: F,
+}
+
+impl<F> KahanSum<F>
+    where F: Float
+{
+    /// Creates an empty sum
+    pub fn new() -> KahanSum<F> {
+        KahanSum {
+            sum: F::zero(),
+            compensation: F::zero(),
+        }
+    }
+
+    /// Adds `value` to the sum
+    pub fn add(&mut self, value: F) {
+        let y = value - self.compensation;
+        let t = self.sum + y;
+        // (t - sum) recovers the high order part of y, subtracting y leaves the lost low bits
+        self.compensation = (t - self.sum) - y;
+        self.sum = t;
+    }
+
+    /// Current value of the sum
+    pub fn sum(&self) -> F {
+        self.sum
+    }
+}
+
+/// Least squares solution `b` of `x * b = y`, found by solving the normal equations
+/// `x^T x b = x^T y`
+///
+/// Each element of `x` is one row of the design matrix, include a column of ones to fit an
+/// offset. Returns `Error::SingularMatrix` if `x^T x` is singular, e.g. because there are fewer
+/// rows than columns, or a column is a linear combination of the others.
+#[cfg(feature="std")]
+pub fn solve_normal_equations(x: &[Vec<f64>], y: &[f64]) -> Result<Vec<f64>, Error> {
+    assert_eq!(x.len(), y.len(), "x and y must have the same number of rows");
+    let n = x.first().map_or(0, |row| row.len());
+
+    // Augmented matrix [X^T X | X^T y]
+    let mut a = vec![vec![0.0; n + 1]; n];
+    for (row, &target) in x.iter().zip(y) {
+        for i in 0..n {
+            for j in 0..n {
+                a[i][j] += row[i] * row[j];
+            }
+            a[i][n] += row[i] * target;
+        }
+    }
+    solve_linear_system(a)
+}
+
+/// Solves the linear system given by the augmented matrix `a` using gaussian elimination with
+/// partial pivoting
+///
+/// `a` has one row per equation, the last column holds the right hand side. Returns
+/// `Error::SingularMatrix` if the system has no unique solution. A pivot counts as zero if it is
+/// not larger than `n * EPSILON` times the largest element of the matrix, so the result does not
+/// depend on the scale of the system.
+#[cfg(feature="std")]
+pub fn solve_linear_system(mut a: Vec<Vec<f64>>) -> Result<Vec<f64>, Error> {
+    let n = a.len();
+    let largest = a.iter()
+        .flat_map(|row| row[..n].iter())
+        .fold(0.0f64, |largest, v| largest.max(v.abs()));
+    let tolerance = n as f64 * ::std::f64::EPSILON * largest;
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[i][k].abs().partial_cmp(&a[j][k].abs()).unwrap())
+            .unwrap();
+        if !(a[pivot][k].abs() > tolerance) {
+            return Err(Error::SingularMatrix);
+        }
+        a.swap(k, pivot);
+        for i in k + 1..n {
+            let factor = a[i][k] / a[k][k];
+            for j in k..n + 1 {
+                a[i][j] -= factor * a[k][j];
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for k in (0..n).rev() {
+        let sum: f64 = (k + 1..n).map(|j| a[k][j] * x[j]).sum();
+        x[k] = (a[k][n] - sum) / a[k][k];
+    }
+    Ok(x)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -113,4 +249,56 @@ mod tests {
 
         assert_eq!(11.0, a.dot(&b))
     }
+
+    #[test]
+    fn kahan_sum() {
+
+        use linear_algebra::KahanSum;
+
+        let gradient = 1e-4f32;
+        let n = 1_000_000;
+        let reference = gradient as f64 * n as f64;
+
+        let mut naive = 0.0f32;
+        let mut kahan = KahanSum::new();
+        for _ in 0..n {
+            naive += gradient;
+            kahan.add(gradient);
+        }
+
+        let naive_error = (naive as f64 - reference).abs();
+        let kahan_error = (kahan.sum() as f64 - reference).abs();
+        assert!(kahan_error < naive_error / 100.0);
+        assert!(kahan_error / reference < 1e-6);
+    }
+
+    #[cfg(feature="std")]
+    #[test]
+    fn normal_equations() {
+
+        use linear_algebra::solve_normal_equations;
+        use Error;
+
+        // y = 2 * x0 - 3 * x1 + 0.5, the last column fits the offset
+        let x = vec![vec![0.0, 0.0, 1.0],
+                     vec![1.0, 0.0, 1.0],
+                     vec![0.0, 1.0, 1.0],
+                     vec![2.0, 3.0, 1.0],
+                     vec![-1.0, 4.0, 1.0]];
+        let y: Vec<f64> = x.iter().map(|row| 2.0 * row[0] - 3.0 * row[1] + 0.5).collect();
+        let b = solve_normal_equations(&x, &y).unwrap();
+        for (actual, expected) in b.iter().zip(&[2.0, -3.0, 0.5]) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+
+        // Second column is twice the first one
+        let x = vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]];
+        assert_eq!(Err(Error::SingularMatrix),
+                   solve_normal_equations(&x, &[1.0, 2.0, 3.0]));
+
+        // Small, but well conditioned
+        let x = vec![vec![1e-7], vec![2e-7], vec![3e-7]];
+        let b = solve_normal_equations(&x, &[1.0, 2.0, 3.0]).unwrap();
+        assert!((b[0] - 1e7).abs() < 1e-3);
+    }
 }
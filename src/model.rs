@@ -1,12 +1,28 @@
-use Model;
+use {Model, Cost, Teacher, Error, learn_history};
 use linear_algebra::Vector;
+#[cfg(feature="std")]
+use linear_algebra::solve_normal_equations;
 use std::marker::PhantomData;
+use num_traits::Float;
+#[cfg(feature="std")]
+use std::cell::RefCell;
+#[cfg(feature="std")]
+use std::collections::HashMap;
+#[cfg(feature="std")]
+use std::hash::Hash;
+#[cfg(feature="std")]
+use cost::CategoricalCrossEntropy;
 
 /// Models the target as a constant `c`
 ///
 /// This model predicts a number. The cost function used during training decides
 /// whether this number is a mean, median, or something else.
 ///
+/// Since the input is ignored, it may be of any type. A history without features
+/// can be built by zipping the truth with `std::iter::repeat(())`. `Constant` is
+/// also a useful baseline to compare other models to. `fit_mean` and `fit_median`
+/// calculate the closed form solutions directly.
+///
 /// # Examples
 ///
 /// Estimate mean
@@ -27,7 +43,8 @@ use std::marker::PhantomData;
 /// learn_history(&teacher, &cost, &mut model, history.iter().cycle().map(|&y|((),y)).take(100));
 /// println!("{}", model.c);
 /// ```
-#[derive(Debug, Default, RustcDecodable, RustcEncodable)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
 pub struct Constant<Input> {
     /// Any prediction made by this model will have the value of `c`
     pub c: f64,
@@ -44,12 +61,92 @@ impl<I> Constant<I> {
     }
 }
 
+#[cfg(feature="std")]
+impl<I> Constant<I> {
+    /// Creates a Constant predicting the mean truth of `history`
+    ///
+    /// This is the model minimizing `cost::LeastSquares`. Predicts `NaN` if `history` is empty.
+    pub fn fit_mean<H>(history: H) -> Constant<I>
+        where H: IntoIterator<Item = (I, f64)>
+    {
+        let (sum, n) = history.into_iter()
+            .fold((0.0, 0.0), |(sum, n), (_, truth)| (sum + truth, n + 1.0));
+        Constant::new(sum / n)
+    }
+
+    /// Creates a Constant predicting the median truth of `history`
+    ///
+    /// This is the model minimizing `cost::LeastAbsoluteDeviation`. For an even number of events
+    /// the mean of the two central truths is used. Predicts `NaN` if `history` is empty.
+    pub fn fit_median<H>(history: H) -> Constant<I>
+        where H: IntoIterator<Item = (I, f64)>
+    {
+        let mut truths: Vec<f64> = history.into_iter().map(|(_, truth)| truth).collect();
+        truths.sort_by(|a, b| a.partial_cmp(b).expect("truth must not be NaN"));
+        let n = truths.len();
+        if n == 0 {
+            Constant::new(::std::f64::NAN)
+        } else if n % 2 == 1 {
+            Constant::new(truths[n / 2])
+        } else {
+            Constant::new((truths[n / 2 - 1] + truths[n / 2]) / 2.0)
+        }
+    }
+
+    /// Creates a Constant predicting the weighted `tau`-quantile of the truths in `history`
+    ///
+    /// Each event is a tuple of features, truth and a non negative weight. The quantile is the
+    /// smallest truth for which the weight of all truths up to and including it exceeds `tau`
+    /// times the total weight. If the cumulative weight hits that fraction exactly, the mean with
+    /// the next larger truth is used, so for equal weights and `tau = 0.5` this matches
+    /// `fit_median`. Events with zero weight are ignored, tied truths are treated as one truth
+    /// with their summed weight. Predicts `NaN` if the total weight is zero.
+    pub fn fit_weighted_quantile<H>(history: H, tau: f64) -> Constant<I>
+        where H: IntoIterator<Item = (I, f64, f64)>
+    {
+        assert!(tau >= 0.0 && tau <= 1.0, "tau must be between 0 and 1");
+        let mut truths: Vec<(f64, f64)> = history.into_iter()
+            .map(|(_, truth, weight)| {
+                assert!(weight >= 0.0, "weight must not be negative");
+                (truth, weight)
+            })
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        truths.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("truth must not be NaN"));
+
+        let total: f64 = truths.iter().map(|&(_, weight)| weight).sum();
+        let target = tau * total;
+        let mut cumulative = 0.0;
+        for (i, &(truth, weight)) in truths.iter().enumerate() {
+            cumulative += weight;
+            let next = truths.get(i + 1).map(|&(next, _)| next);
+            // Accumulate the weight of all ties before comparing
+            if next == Some(truth) {
+                continue;
+            }
+            if cumulative > target {
+                return Constant::new(truth);
+            }
+            if cumulative == target {
+                return Constant::new(next.map_or(truth, |next| (truth + next) / 2.0));
+            }
+        }
+        Constant::new(f64::NAN)
+    }
+}
+
 impl<I> Clone for Constant<I> {
     fn clone(&self) -> Self {
         Constant::new(self.c)
     }
 }
 
+impl<I> PartialEq for Constant<I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.c == other.c
+    }
+}
+
 impl<I> Model for Constant<I> {
     type Input = I;
 
@@ -77,7 +174,8 @@ impl<I> Model for Constant<I> {
 }
 
 /// Models the target as `y = m * x + c`
-#[derive(Debug, Clone, Default, RustcDecodable, RustcEncodable)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
 pub struct Linear<V: Vector> {
     /// Slope
     pub m: V,
@@ -85,62 +183,1360 @@ pub struct Linear<V: Vector> {
     pub c: V::Scalar,
 }
 
-impl<V> Model for Linear<V>
+/// Models whose prediction is an `activation` function of `m * x + c` (generalized linear models)
+///
+/// Every implementor is a `Model`, whose coefficents are those of the underlying `Linear`. Adding
+/// a new model of this family only requires the activation and its derivative.
+pub trait GeneralizedLinearModel: Clone {
+    /// Input features
+    type Features: Vector<Scalar = f64>;
+
+    /// Weights and offset of the linear combination
+    fn linear(&self) -> &Linear<Self::Features>;
+
+    /// Mutable access to the weights and offset of the linear combination
+    fn linear_mut(&mut self) -> &mut Linear<Self::Features>;
+
+    /// Prediction for the linear combination `m * x + c` of the input
+    fn activation(&self, linear_combination: f64) -> f64;
+
+    /// `activation` derived by the linear combination
+    fn activation_derivative(&self, linear_combination: f64) -> f64;
+}
+
+impl<G> Model for G
+    where G: GeneralizedLinearModel
+{
+    type Input = G::Features;
+
+    fn predict(&self, input: &G::Features) -> f64 {
+        let linear = self.linear();
+        self.activation(linear.m.dot(input) + linear.c)
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.linear().m.dimension() + 1
+    }
+
+    fn gradient(&self, coefficent: usize, input: &G::Features) -> f64 {
+        let linear = self.linear();
+        let outer = self.activation_derivative(linear.m.dot(input) + linear.c);
+        if coefficent == linear.m.dimension() {
+            outer //c
+        } else {
+            outer * input.at(coefficent)
+        }
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        let linear = self.linear_mut();
+        if coefficent == linear.m.dimension() {
+            &mut linear.c
+        } else {
+            linear.m.mut_at(coefficent)
+        }
+    }
+
+    fn validate(&self, sample_input: &G::Features) -> Result<(), Error> {
+        let dimension = self.linear().m.dimension();
+        if sample_input.dimension() == dimension {
+            Ok(())
+        } else {
+            Err(Error::DimensionMismatch {
+                expected: dimension,
+                actual: sample_input.dimension(),
+            })
+        }
+    }
+}
+
+/// Points on the decision boundary of a classifier with two features, for plotting
+///
+/// The boundary is the line on which the linear combination `m0*x + m1*y + c` is zero, e.g.
+/// where `Logistic` predicts `0.5`. Returns `points` points with `x` evenly spaced from
+/// `x_range.0` to `x_range.1` and `y` solved from the equation above. If `m1` is zero the boundary
+/// is a vertical line, which can not be expressed this way, and no points are returned.
+#[cfg(feature="std")]
+pub fn decision_boundary<G>(model: &G, x_range: (f64, f64), points: usize) -> Vec<(f64, f64)>
+    where G: GeneralizedLinearModel<Features = [f64; 2]>
+{
+    let linear = model.linear();
+    if linear.m[1] == 0.0 {
+        return Vec::new();
+    }
+    let (start, end) = x_range;
+    (0..points)
+        .map(|i| {
+            let x = if points > 1 {
+                start + (end - start) * i as f64 / (points - 1) as f64
+            } else {
+                start
+            };
+            (x, -(linear.m[0] * x + linear.c) / linear.m[1])
+        })
+        .collect()
+}
+
+impl<V> GeneralizedLinearModel for Linear<V>
+    where V: Vector<Scalar = f64>
+{
+    type Features = V;
+
+    fn linear(&self) -> &Linear<V> {
+        self
+    }
+
+    fn linear_mut(&mut self) -> &mut Linear<V> {
+        self
+    }
+
+    fn activation(&self, linear_combination: f64) -> f64 {
+        linear_combination
+    }
+
+    fn activation_derivative(&self, _: f64) -> f64 {
+        1.0
+    }
+}
+
+impl<V> Linear<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Multiplies each feature with a constant factor before it is weighted
+    ///
+    /// The `scales` are not trained. See `FeatureScaled`.
+    ///
+    /// # Panics
+    ///
+    /// If `scales` and the weights differ in dimension.
+    pub fn with_feature_scales(self, scales: V) -> FeatureScaled<V> {
+        assert_eq!(self.m.dimension(),
+                   scales.dimension(),
+                   "there must be one scale for each weight");
+        FeatureScaled {
+            linear: self,
+            scales: scales,
+        }
+    }
+}
+
+/// Models the target as `y = m * (s x) + c`, with `s x` being the element-wise product of the
+/// constant `scales` and the input
+///
+/// Only `m` and `c` are coefficents, the scales encode prior knowledge about the importance of
+/// each feature. Since the gradient of a weight is proportional to its scale, features with a
+/// larger scale are learned faster, and a feature with twice the scale ends up with half the
+/// weight for the same fit. Created by `Linear::with_feature_scales`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
+pub struct FeatureScaled<V: Vector> {
+    /// Trained weights and offset
+    pub linear: Linear<V>,
+    /// Constant factor of each feature
+    pub scales: V,
+}
+
+impl<V> Model for FeatureScaled<V>
     where V: Vector<Scalar = f64>
 {
     type Input = V;
 
-    fn predict(&self, input: &V) -> V::Scalar {
-        self.m.dot(input) + self.c
+    fn predict(&self, input: &V) -> f64 {
+        let mut sum = 0.0;
+        for i in 0..self.scales.dimension() {
+            sum += self.linear.m.at(i) * self.scales.at(i) * input.at(i);
+        }
+        sum + self.linear.c
     }
 
     fn num_coefficents(&self) -> usize {
-        self.m.dimension() + 1
+        self.linear.num_coefficents()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &V) -> f64 {
+        if coefficent == self.scales.dimension() {
+            1.0 //c
+        } else {
+            self.scales.at(coefficent) * input.at(coefficent)
+        }
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        self.linear.coefficent(coefficent)
+    }
+
+    fn validate(&self, sample_input: &V) -> Result<(), Error> {
+        if self.scales.dimension() != self.linear.m.dimension() {
+            return Err(Error::DimensionMismatch {
+                expected: self.linear.m.dimension(),
+                actual: self.scales.dimension(),
+            });
+        }
+        self.linear.validate(sample_input)
+    }
+}
+
+#[cfg(feature="std")]
+impl Linear<Vec<f64>> {
+    /// Appends a weight for an additional feature, initialized to `initial`
+    ///
+    /// Inputs must contain the new feature as their last entry afterwards. Allows to grow a
+    /// model during forward feature selection.
+    pub fn add_feature(&mut self, initial: f64) {
+        self.m.push(initial);
+    }
+}
+
+/// Models whose number of coefficents can change during training
+///
+/// Allows training algorithms which grow a model, e.g. stepwise feature selection, to resize it
+/// without knowing its concrete type.
+#[cfg(feature="std")]
+pub trait GrowableModel: Model {
+    /// Appends a coefficent initialized to `value`
+    fn push_coefficent(&mut self, value: f64);
+
+    /// Removes the most recently pushed coefficents, keeping the first `n`
+    ///
+    /// Coefficents which are part of every instance of the model, e.g. the offset of `Linear`,
+    /// are never removed, so `num_coefficents` may stay larger than `n`.
+    fn truncate(&mut self, n: usize);
+}
+
+/// Coefficents are pushed as weights of additional features, see `Linear::add_feature`
+#[cfg(feature="std")]
+impl GrowableModel for Linear<Vec<f64>> {
+    fn push_coefficent(&mut self, value: f64) {
+        self.add_feature(value);
+    }
+
+    fn truncate(&mut self, n: usize) {
+        // The offset is the last coefficent and always kept
+        self.m.truncate(n.saturating_sub(1));
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> Linear<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Gradient of the prediction by each feature of `input`, which is just `m`
+    pub fn input_gradient(&self, _input: &V) -> Vec<f64> {
+        (0..self.m.dimension()).map(|i| self.m.at(i)).collect()
+    }
+
+    /// Predicts a target for each of `rows`
+    ///
+    /// Same as calling `predict` for every row, e.g. to score many fixed dimension inputs like
+    /// `&[[f64; 2]]` at once.
+    pub fn predict_rows(&self, rows: &[V]) -> Vec<f64> {
+        let mut predictions = Vec::with_capacity(rows.len());
+        for row in rows {
+            predictions.push(self.m.dot(row) + self.c);
+        }
+        predictions
+    }
+}
+
+/// Models which can be constructed from a flat vector of coefficents
+///
+/// Inverse of `vikos::coefficents`, so the coefficents can be round-tripped through e.g. an
+/// external optimizer. Coefficents are expected in the order of their index in `Model`.
+pub trait FromCoefficents: Model + Sized {
+    /// Creates the model with the coefficents `coefficents`
+    ///
+    /// Fails with `Error::CoefficentCountMismatch` if the number of coefficents does not fit the
+    /// model.
+    fn from_coefficents(coefficents: &[f64]) -> Result<Self, Error>;
+}
+
+impl<I> FromCoefficents for Constant<I> {
+    fn from_coefficents(coefficents: &[f64]) -> Result<Constant<I>, Error> {
+        if coefficents.len() != 1 {
+            return Err(Error::CoefficentCountMismatch {
+                expected: 1,
+                actual: coefficents.len(),
+            });
+        }
+        Ok(Constant::new(coefficents[0]))
+    }
+}
+
+/// Weights followed by the offset. Any number of at least one coefficent is accepted.
+#[cfg(feature="std")]
+impl FromCoefficents for Linear<Vec<f64>> {
+    fn from_coefficents(coefficents: &[f64]) -> Result<Linear<Vec<f64>>, Error> {
+        match coefficents.split_last() {
+            Some((&c, m)) => Ok(Linear { m: m.to_vec(), c: c }),
+            None => {
+                Err(Error::CoefficentCountMismatch {
+                    expected: 1,
+                    actual: 0,
+                })
+            }
+        }
+    }
+}
+
+macro_rules! from_coefficents_for_arrays {
+    ($($v:expr)*) => {
+        $(
+            impl FromCoefficents for Linear<[f64; $v]> {
+                fn from_coefficents(coefficents: &[f64]) -> Result<Linear<[f64; $v]>, Error> {
+                    if coefficents.len() != $v + 1 {
+                        return Err(Error::CoefficentCountMismatch {
+                            expected: $v + 1,
+                            actual: coefficents.len(),
+                        });
+                    }
+                    let mut m = [0.0; $v];
+                    m.copy_from_slice(&coefficents[..$v]);
+                    Ok(Linear { m: m, c: coefficents[$v] })
+                }
+            }
+        )*
+    }
+}
+
+from_coefficents_for_arrays! { 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26
+                               27 28 29 30 31 32 }
+
+/// Models target as `y = 1/(1+e^(m * x + c))`
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
+pub struct Logistic<V: Vector>(Linear<V>);
+
+impl<V> Logistic<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Log-odds of the prediction, i.e. `-(m * x + c)`
+    ///
+    /// Prefer this over calculating the logit of `predict`, which loses precision close to
+    /// zero or one.
+    pub fn predict_logit(&self, input: &V) -> f64 {
+        -self.0.predict(input)
+    }
+
+    /// Probability of the positive class, same as `predict`
+    pub fn predict_proba(&self, input: &V) -> f64 {
+        self.predict(input)
+    }
+
+    /// `true` if the probability of the positive class is at least `threshold`
+    ///
+    /// With a `threshold` of `0.5` this is the more likely class.
+    pub fn predict_class(&self, input: &V, threshold: f64) -> bool {
+        self.predict_proba(input) >= threshold
+    }
+
+    /// Creates a model predicting the probability `p` for any input
+    ///
+    /// The weights are set to `zero`, which must have all elements zero and the dimension of
+    /// the features, e.g. `vec![0.0; n]` for `Logistic<Vec<f64>>`. The offset is set to the
+    /// log-odds of `p`. Initializing with the base rate of the positive class speeds up training
+    /// on imbalanced data considerably.
+    ///
+    /// # Panics
+    ///
+    /// If `p` is not in the open interval `(0, 1)`.
+    pub fn with_base_rate(zero: V, p: f64) -> Logistic<V> {
+        assert!(p > 0.0 && p < 1.0,
+                "base rate must be between 0 and 1 exclusive, got {}",
+                p);
+        Logistic(Linear {
+            m: zero,
+            // Prediction is 1/(1+e^c), so c is the negative log-odds
+            c: Float::ln((1.0 - p) / p),
+        })
+    }
+}
+
+impl<V> GeneralizedLinearModel for Logistic<V>
+    where V: Vector<Scalar = f64>
+{
+    type Features = V;
+
+    fn linear(&self) -> &Linear<V> {
+        &self.0
+    }
+
+    fn linear_mut(&mut self) -> &mut Linear<V> {
+        &mut self.0
+    }
+
+    fn activation(&self, linear_combination: f64) -> f64 {
+        1.0 / (1.0 + Float::exp(linear_combination))
+    }
+
+    fn activation_derivative(&self, linear_combination: f64) -> f64 {
+        let p = self.activation(linear_combination);
+        -p * (1.0 - p)
+    }
+}
+
+impl<V> FromCoefficents for Logistic<V>
+    where V: Vector<Scalar = f64>,
+          Linear<V>: FromCoefficents
+{
+    fn from_coefficents(coefficents: &[f64]) -> Result<Logistic<V>, Error> {
+        Linear::from_coefficents(coefficents).map(Logistic)
     }
+}
 
-    fn gradient(&self, coefficent: usize, input: &V) -> V::Scalar {
+#[cfg(feature="std")]
+impl GrowableModel for Logistic<Vec<f64>> {
+    fn push_coefficent(&mut self, value: f64) {
+        self.0.push_coefficent(value);
+    }
 
-        use num::One;
+    fn truncate(&mut self, n: usize) {
+        self.0.truncate(n);
+    }
+}
 
-        if coefficent == self.m.dimension() {
-            V::Scalar::one() //c
+#[cfg(feature="rand")]
+impl<V> Logistic<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Draws `1` with probability `predict(input)` and `0` otherwise
+    ///
+    /// Useful to simulate labeled data. The draw is determined by `rng`.
+    pub fn sample<R>(&self, input: &V, rng: &mut R) -> f64
+        where R: ::rand::Rng
+    {
+        if rng.next_f64() < self.predict(input) {
+            1.0
         } else {
-            input.at(coefficent)
+            0.0
         }
     }
+}
 
-    fn coefficent(&mut self, coefficent: usize) -> &mut V::Scalar {
-        if coefficent == self.m.dimension() {
-            &mut self.c
+#[cfg(feature="std")]
+impl<V> Logistic<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Gradient of the prediction by each feature of `input`
+    ///
+    /// The weights scaled with the derivative of the sigmoid at `input`.
+    pub fn input_gradient(&self, input: &V) -> Vec<f64> {
+        let p = self.predict(input);
+        self.0.input_gradient(input).into_iter().map(|m| -p * (1.0 - p) * m).collect()
+    }
+
+    /// Predicts a target for each of `rows`
+    ///
+    /// Same as calling `predict` for every row.
+    pub fn predict_rows(&self, rows: &[V]) -> Vec<f64> {
+        let mut predictions = self.0.predict_rows(rows);
+        for p in &mut predictions {
+            *p = 1.0 / (1.0 + Float::exp(*p));
+        }
+        predictions
+    }
+}
+
+/// Models target as `y = e^(m * x + c)` (log link)
+///
+/// Predictions are always positive, which suits counts or amounts, e.g. together with
+/// `cost::Tweedie`. Large values of `m * x + c` overflow, so start training with small weights.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
+pub struct Exponential<V: Vector>(Linear<V>);
+
+impl<V> GeneralizedLinearModel for Exponential<V>
+    where V: Vector<Scalar = f64>
+{
+    type Features = V;
+
+    fn linear(&self) -> &Linear<V> {
+        &self.0
+    }
+
+    fn linear_mut(&mut self) -> &mut Linear<V> {
+        &mut self.0
+    }
+
+    fn activation(&self, linear_combination: f64) -> f64 {
+        Float::exp(linear_combination)
+    }
+
+    fn activation_derivative(&self, linear_combination: f64) -> f64 {
+        Float::exp(linear_combination)
+    }
+}
+
+/// Models target as `y = ln(1+e^(m * x + c))`
+///
+/// Predictions are always positive. In contrast to an exponential link, the prediction only
+/// grows linearly for large `m * x + c`, which makes it less prone to overflow.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
+pub struct Softplus<V: Vector>(Linear<V>);
+
+impl<V> GeneralizedLinearModel for Softplus<V>
+    where V: Vector<Scalar = f64>
+{
+    type Features = V;
+
+    fn linear(&self) -> &Linear<V> {
+        &self.0
+    }
+
+    fn linear_mut(&mut self) -> &mut Linear<V> {
+        &mut self.0
+    }
+
+    fn activation(&self, linear_combination: f64) -> f64 {
+        // ln(1+e^x) = x + ln(1+e^-x), avoids overflow for large x
+        if linear_combination > 0.0 {
+            linear_combination + Float::ln_1p(Float::exp(-linear_combination))
+        } else {
+            Float::ln_1p(Float::exp(linear_combination))
+        }
+    }
+
+    fn activation_derivative(&self, linear_combination: f64) -> f64 {
+        1.0 / (1.0 + Float::exp(-linear_combination))
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> Softplus<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Gradient of the prediction by each feature of `input`
+    pub fn input_gradient(&self, input: &V) -> Vec<f64> {
+        let sigmoid = 1.0 / (1.0 + Float::exp(-self.0.predict(input)));
+        self.0.input_gradient(input).into_iter().map(|m| sigmoid * m).collect()
+    }
+}
+
+/// Models an ordinal target with `K` ordered classes `0, 1, ..., K-1`
+///
+/// Uses the cumulative logit approach: The probability of the target being
+/// larger than class `k` is `P(y > k) = 1/(1+e^(t_k - m * x))` where `t_k` are
+/// `K-1` ascending thresholds. The prediction is the expected class
+/// `sum P(y > k)`.
+///
+/// Since training algorithms change the coefficents independently of each
+/// other, the thresholds are not stored directly. Only the lowest threshold
+/// `c` is, every other threshold is the previous one plus `e^g` for the
+/// respective entry `g` in `log_gaps`. This way the thresholds stay ordered
+/// no matter how they are updated.
+///
+/// The coefficents are the entries of `m`, followed by `c` and `log_gaps`.
+#[cfg(feature="std")]
+#[derive(Debug, Clone, Default, PartialEq, RustcDecodable, RustcEncodable)]
+pub struct OrdinalRegression<V: Vector> {
+    /// Weights of the linear score
+    pub m: V,
+    /// Lowest threshold
+    pub c: f64,
+    /// Logarithms of the distances between consecutive thresholds
+    pub log_gaps: Vec<f64>,
+}
+
+#[cfg(feature="std")]
+impl<V> OrdinalRegression<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Creates a model for `classes` ordinal classes with thresholds one apart
+    ///
+    /// `classes` must be at least two.
+    pub fn new(m: V, classes: usize) -> OrdinalRegression<V> {
+        assert!(classes >= 2, "ordinal regression requires at least two classes");
+        let mut log_gaps = Vec::with_capacity(classes - 2);
+        log_gaps.resize(classes - 2, 0.0);
+        OrdinalRegression {
+            m: m,
+            c: -((classes - 2) as f64) / 2.0,
+            log_gaps: log_gaps,
+        }
+    }
+
+    /// The `K-1` ascending thresholds between the classes
+    pub fn thresholds(&self) -> Vec<f64> {
+        let mut thresholds = Vec::with_capacity(self.log_gaps.len() + 1);
+        let mut t = self.c;
+        thresholds.push(t);
+        for g in &self.log_gaps {
+            t = t + g.exp();
+            thresholds.push(t);
+        }
+        thresholds
+    }
+
+    // Derivatives of `P(y > k)` by the linear score for every threshold `k`
+    fn slopes(&self, input: &V) -> Vec<f64> {
+        let score = self.m.dot(input);
+        self.thresholds()
+            .iter()
+            .map(|t| {
+                let p = 1.0 / (1.0 + (t - score).exp());
+                p * (1.0 - p)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> Model for OrdinalRegression<V>
+    where V: Vector<Scalar = f64>
+{
+    type Input = V;
+
+    fn predict(&self, input: &V) -> f64 {
+        let score = self.m.dot(input);
+        self.thresholds().iter().map(|t| 1.0 / (1.0 + (t - score).exp())).sum()
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.m.dimension() + 1 + self.log_gaps.len()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &V) -> f64 {
+        let slopes = self.slopes(input);
+        let dim = self.m.dimension();
+        if coefficent < dim {
+            input.at(coefficent) * slopes.iter().sum::<f64>()
+        } else if coefficent == dim {
+            -slopes.iter().sum::<f64>()
         } else {
+            // Gap `g` shifts all thresholds above it
+            let g = coefficent - dim - 1;
+            -self.log_gaps[g].exp() * slopes[g + 1..].iter().sum::<f64>()
+        }
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        let dim = self.m.dimension();
+        if coefficent < dim {
             self.m.mut_at(coefficent)
+        } else if coefficent == dim {
+            &mut self.c
+        } else {
+            &mut self.log_gaps[coefficent - dim - 1]
+        }
+    }
+
+    fn validate(&self, sample_input: &V) -> Result<(), Error> {
+        if sample_input.dimension() == self.m.dimension() {
+            Ok(())
+        } else {
+            Err(Error::DimensionMismatch {
+                expected: self.m.dimension(),
+                actual: sample_input.dimension(),
+            })
         }
     }
 }
 
-/// Models target as `y = 1/(1+e^(m * x + c))`
-#[derive(Debug, Clone, Default, RustcDecodable, RustcEncodable)]
-pub struct Logistic<V: Vector>(Linear<V>);
+/// Models the probabilities of `K` unordered classes `0, 1, ..., K-1` (multinomial logistic
+/// regression)
+///
+/// Each class has its own linear score (logit) `m_k * x + c_k`. The probability of class `k` is
+/// `e^(score_k) / sum e^(score_j)`. Since the prediction is a probability vector rather than a
+/// single number, `Softmax` does not implement `Model`. Train it with `teach_event` and
+/// `cost::CategoricalCrossEntropy`.
+#[cfg(feature="std")]
+#[derive(Debug, Clone, Default, PartialEq, RustcDecodable, RustcEncodable)]
+pub struct Softmax<V: Vector> {
+    /// Linear score of each class
+    pub scores: Vec<Linear<V>>,
+}
+
+#[cfg(feature="std")]
+impl<V> Softmax<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Creates a model for `classes` classes, all starting with the weights `m` and no offset
+    pub fn new(m: V, classes: usize) -> Softmax<V> {
+        Softmax {
+            scores: (0..classes)
+                .map(|_| {
+                    Linear {
+                        m: m.clone(),
+                        c: 0.0,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Linear score of each class
+    pub fn logits(&self, input: &V) -> Vec<f64> {
+        self.scores.iter().map(|score| score.predict(input)).collect()
+    }
+
+    /// Probability of each class
+    pub fn predict_proba(&self, input: &V) -> Vec<f64> {
+        let logits = self.logits(input);
+        // Subtracting the largest logit keeps `exp` from overflowing
+        let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let exp: Vec<f64> = logits.iter().map(|l| (l - max).exp()).collect();
+        let sum: f64 = exp.iter().sum();
+        exp.into_iter().map(|e| e / sum).collect()
+    }
+
+    /// Index of the most likely class
+    pub fn predict_class(&self, input: &V) -> usize {
+        let logits = self.logits(input);
+        (0..logits.len())
+            .fold(0, |best, k| if logits[k] > logits[best] { k } else { best })
+    }
+
+    /// Gradient descent step for an event of class `class`
+    ///
+    /// Every weight moves by `learning_rate` times the gradient of `cost` by the logits, times
+    /// the derivative of the logit by the weight.
+    pub fn teach_event(&mut self,
+                       cost: &CategoricalCrossEntropy,
+                       input: &V,
+                       class: usize,
+                       learning_rate: f64) {
+        let gradient = cost.logit_gradient(&self.predict_proba(input), class);
+        for (score, g) in self.scores.iter_mut().zip(gradient) {
+            for ci in 0..score.num_coefficents() {
+                let derivative = score.gradient(ci, input);
+                *score.coefficent(ci) -= learning_rate * g * derivative;
+            }
+        }
+    }
+}
+
+/// Predicts `left` if the `feature`-th component of the input is smaller than `threshold` and
+/// `right` otherwise
+///
+/// Only `left` and `right` are coefficents and can be trained by gradient based algorithms.
+/// `feature` and `threshold` are chosen by fitting the stump to a history.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature="std", derive(RustcDecodable, RustcEncodable))]
+pub struct DecisionStump<V> {
+    /// Index of the feature the split is based on
+    pub feature: usize,
+    /// Inputs with a smaller `feature` are predicted as `left`, all others as `right`
+    pub threshold: f64,
+    /// Prediction for inputs below the threshold
+    pub left: f64,
+    /// Prediction for inputs at or above the threshold
+    pub right: f64,
+    _phantom: PhantomData<V>,
+}
 
-impl<V> Model for Logistic<V>
+impl<V> DecisionStump<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Creates a new stump splitting the `feature`-th component at `threshold`
+    pub fn new(feature: usize, threshold: f64, left: f64, right: f64) -> DecisionStump<V> {
+        DecisionStump {
+            feature: feature,
+            threshold: threshold,
+            left: left,
+            right: right,
+            _phantom: PhantomData {},
+        }
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> DecisionStump<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Chooses the split minimizing the squared error of `history`
+    ///
+    /// Only the features listed in `features` are considered as split feature. The leaves
+    /// predict the mean truth of their events. `history` must not be empty.
+    pub fn fit(history: &[(V, f64)], features: &[usize]) -> DecisionStump<V> {
+        assert!(!history.is_empty(), "can not fit a decision stump to an empty history");
+        let total_sum: f64 = history.iter().map(|&(_, t)| t).sum();
+        let total_n = history.len() as f64;
+        let mean = total_sum / total_n;
+        // Without a split, every event is predicted within the right leaf
+        let mut best = DecisionStump::new(0, ::std::f64::NEG_INFINITY, mean, mean);
+        let mut best_score = total_sum * mean;
+
+        for &feature in features {
+            let mut events: Vec<(f64, f64)> =
+                history.iter().map(|&(ref x, t)| (x.at(feature), t)).collect();
+            events.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("features must not be NaN"));
+
+            let mut left_sum = 0.0;
+            for i in 1..events.len() {
+                left_sum = left_sum + events[i - 1].1;
+                if events[i - 1].0 == events[i].0 {
+                    continue;
+                }
+                let left_n = i as f64;
+                let right_sum = total_sum - left_sum;
+                // Minimizing the squared error is equivalent to maximizing this score
+                let score = left_sum * left_sum / left_n +
+                            right_sum * right_sum / (total_n - left_n);
+                if score > best_score {
+                    best_score = score;
+                    best = DecisionStump::new(feature,
+                                              (events[i - 1].0 + events[i].0) / 2.0,
+                                              left_sum / left_n,
+                                              right_sum / (total_n - left_n));
+                }
+            }
+        }
+        best
+    }
+
+    /// Like `fit`, but only considers a random subset of the features
+    ///
+    /// `subset_size` features are drawn without replacement using `rng`. This decorrelates the
+    /// members of an ensemble of stumps, as used in random forests.
+    #[cfg(feature="rand")]
+    pub fn fit_random_subset<R>(history: &[(V, f64)],
+                                subset_size: usize,
+                                rng: &mut R)
+                                -> DecisionStump<V>
+        where R: ::rand::Rng
+    {
+        let dimension = history.first().map_or(0, |&(ref x, _)| x.dimension());
+        let mut features: Vec<usize> = (0..dimension).collect();
+        rng.shuffle(&mut features);
+        features.truncate(subset_size);
+        DecisionStump::fit(history, &features)
+    }
+
+    /// Renders the split and both leaves as a Graphviz DOT graph
+    ///
+    /// Visualize the output e.g. with `dot -Tpng`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        dot.push_str(&format!("    split [label=\"x{} < {}\"];\n", self.feature, self.threshold));
+        dot.push_str(&format!("    left [label=\"{}\", shape=box];\n", self.left));
+        dot.push_str(&format!("    right [label=\"{}\", shape=box];\n", self.right));
+        dot.push_str("    split -> left [label=\"yes\"];\n");
+        dot.push_str("    split -> right [label=\"no\"];\n");
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<V> Model for DecisionStump<V>
     where V: Vector<Scalar = f64>
 {
     type Input = V;
 
     fn predict(&self, input: &V) -> f64 {
-        1.0 / (1.0 + self.0.predict(input).exp())
+        if input.at(self.feature) < self.threshold {
+            self.left
+        } else {
+            self.right
+        }
     }
 
     fn num_coefficents(&self) -> usize {
-        self.0.num_coefficents()
+        2
     }
 
     fn gradient(&self, coefficent: usize, input: &V) -> f64 {
-        let p = self.predict(input);
-        -p * (1.0 - p) * self.0.gradient(coefficent, input)
+        let is_left = input.at(self.feature) < self.threshold;
+        match coefficent {
+            0 => if is_left { 1.0 } else { 0.0 },
+            1 => if is_left { 0.0 } else { 1.0 },
+            _ => panic!("coefficent index out of range"),
+        }
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        match coefficent {
+            0 => &mut self.left,
+            1 => &mut self.right,
+            _ => panic!("coefficent index out of range"),
+        }
+    }
+
+    fn validate(&self, sample_input: &V) -> Result<(), Error> {
+        if self.feature < sample_input.dimension() {
+            Ok(())
+        } else {
+            Err(Error::DimensionMismatch {
+                expected: self.feature + 1,
+                actual: sample_input.dimension(),
+            })
+        }
+    }
+}
+
+/// Wraps `model`, which predicts the target in a transformed space, e.g. its logarithm
+///
+/// `predict` applies `inverse` to the prediction of `model`, so it returns values in the
+/// original space. Use `learn_transformed` to train `model` on the `forward` transformed truth,
+/// which minimizes the error in the transformed space. Teaching the wrapper directly minimizes
+/// the error in the original space instead, accounting for the transform in its gradient.
+#[derive(Debug, Clone)]
+pub struct TargetTransform<M> {
+    /// Model predicting the transformed target
+    pub model: M,
+    /// Transforms the truth into the space predicted by `model`
+    pub forward: fn(f64) -> f64,
+    /// Inverse of `forward`
+    pub inverse: fn(f64) -> f64,
+    /// Derivative of `inverse`
+    pub inverse_derivative: fn(f64) -> f64,
+}
+
+impl<M> TargetTransform<M> {
+    /// `model` predicts the natural logarithm of the target, which suits right skewed targets
+    ///
+    /// All truths must be positive.
+    pub fn log(model: M) -> TargetTransform<M> {
+        TargetTransform {
+            model: model,
+            forward: Float::ln,
+            inverse: Float::exp,
+            inverse_derivative: Float::exp,
+        }
+    }
+
+    /// Teaches `model` all events in `history`, with the truth transformed by `forward`
+    pub fn learn_transformed<C, T, H>(&mut self, teacher: &T, cost: &C, history: H)
+        where M: Model,
+              C: Cost<f64>,
+              T: Teacher<M>,
+              H: IntoIterator<Item = (M::Input, f64)>
+    {
+        let forward = self.forward;
+        learn_history(teacher,
+                      cost,
+                      &mut self.model,
+                      history.into_iter().map(|(input, truth)| (input, forward(truth))));
+    }
+}
+
+impl<M> Model for TargetTransform<M>
+    where M: Model
+{
+    type Input = M::Input;
+
+    fn predict(&self, input: &M::Input) -> f64 {
+        (self.inverse)(self.model.predict(input))
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.model.num_coefficents()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &M::Input) -> f64 {
+        (self.inverse_derivative)(self.model.predict(input)) *
+        self.model.gradient(coefficent, input)
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        self.model.coefficent(coefficent)
+    }
+
+    fn validate(&self, sample_input: &M::Input) -> Result<(), Error> {
+        self.model.validate(sample_input)
+    }
+}
+
+/// Model defined by closures operating on a vector of coefficents
+///
+/// Allows prototyping custom models without implementing `Model`. `predict` receives the
+/// coefficents and the input, `gradient` additionally the index of the coefficent to derive
+/// the prediction by.
+#[cfg(feature="std")]
+pub struct ClosureModel<I, P, G> {
+    /// Coefficents changed by the training
+    pub coefficents: Vec<f64>,
+    predict: P,
+    gradient: G,
+    _phantom: PhantomData<I>,
+}
+
+#[cfg(feature="std")]
+impl<I, P, G> ClosureModel<I, P, G>
+    where P: Fn(&[f64], &I) -> f64 + Clone,
+          G: Fn(&[f64], usize, &I) -> f64 + Clone
+{
+    /// Creates a model from initial `coefficents` and the `predict` and `gradient` closures
+    pub fn new(coefficents: Vec<f64>, predict: P, gradient: G) -> ClosureModel<I, P, G> {
+        ClosureModel {
+            coefficents: coefficents,
+            predict: predict,
+            gradient: gradient,
+            _phantom: PhantomData {},
+        }
+    }
+}
+
+#[cfg(feature="std")]
+impl<I, P, G> Clone for ClosureModel<I, P, G>
+    where P: Clone,
+          G: Clone
+{
+    fn clone(&self) -> Self {
+        ClosureModel {
+            coefficents: self.coefficents.clone(),
+            predict: self.predict.clone(),
+            gradient: self.gradient.clone(),
+            _phantom: PhantomData {},
+        }
+    }
+}
+
+#[cfg(feature="std")]
+impl<I, P, G> Model for ClosureModel<I, P, G>
+    where P: Fn(&[f64], &I) -> f64 + Clone,
+          G: Fn(&[f64], usize, &I) -> f64 + Clone
+{
+    type Input = I;
+
+    fn predict(&self, input: &I) -> f64 {
+        (self.predict)(&self.coefficents, input)
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.coefficents.len()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &I) -> f64 {
+        (self.gradient)(&self.coefficents, coefficent, input)
     }
 
     fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
-        self.0.coefficent(coefficent)
+        &mut self.coefficents[coefficent]
+    }
+}
+
+/// Ensemble combining the predictions of several `base` models with a `Linear` `meta` model
+///
+/// The base models may be of different types. Since they are trained independently of the
+/// meta model, `Stacking` only implements `DynModel`. See `model_selection::fit_stacking`.
+#[cfg(feature="std")]
+pub struct Stacking<I> {
+    /// Models whose predictions are the input of `meta`
+    pub base: Vec<Box<dyn super::DynModel<I>>>,
+    /// Combines the predictions of the base models
+    pub meta: Linear<Vec<f64>>,
+}
+
+#[cfg(feature="std")]
+impl<I> Stacking<I> {
+    /// Predictions of the base models for `input`, which are the input of `meta`
+    pub fn base_predictions(&self, input: &I) -> Vec<f64> {
+        self.base.iter().map(|model| model.predict_dyn(input)).collect()
+    }
+}
+
+#[cfg(feature="std")]
+impl<I> super::DynModel<I> for Stacking<I> {
+    fn predict_dyn(&self, input: &I) -> f64 {
+        Model::predict(&self.meta, &self.base_predictions(input))
+    }
+}
+
+/// Memoizes the predictions of `model` for repeated inputs
+///
+/// Trades memory for speed if the same inputs are scored many times between changes of the
+/// coefficents. Any mutable access to a coefficent clears the cache, so predictions are never
+/// stale. Since training accesses the coefficents for every event, wrapping a model during
+/// training only incurs overhead.
+#[cfg(feature="std")]
+#[derive(Debug, Clone)]
+pub struct CachingModel<M: Model> {
+    model: M,
+    cache: RefCell<HashMap<M::Input, f64>>,
+}
+
+#[cfg(feature="std")]
+impl<M> CachingModel<M>
+    where M: Model,
+          M::Input: Hash + Eq + Clone
+{
+    /// Wraps `model` with an empty cache
+    pub fn new(model: M) -> CachingModel<M> {
+        CachingModel {
+            model: model,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The wrapped model
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Unwraps the model, dropping the cache
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    /// Number of currently cached predictions
+    pub fn cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+#[cfg(feature="std")]
+impl<M> Model for CachingModel<M>
+    where M: Model,
+          M::Input: Hash + Eq + Clone
+{
+    type Input = M::Input;
+
+    fn predict(&self, input: &M::Input) -> f64 {
+        if let Some(&prediction) = self.cache.borrow().get(input) {
+            return prediction;
+        }
+        let prediction = self.model.predict(input);
+        self.cache.borrow_mut().insert(input.clone(), prediction);
+        prediction
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.model.num_coefficents()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &M::Input) -> f64 {
+        self.model.gradient(coefficent, input)
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        // The caller may change the coefficent through the returned reference
+        self.cache.get_mut().clear();
+        self.model.coefficent(coefficent)
+    }
+
+    fn validate(&self, sample_input: &M::Input) -> Result<(), Error> {
+        self.model.validate(sample_input)
+    }
+}
+
+/// Calibrates the scores of `model` into probabilities (Platt scaling)
+///
+/// A logistic regression maps the prediction of `model` to the probability of the positive
+/// class. Fit it with `calibrate` on events held out of the training of `model`, otherwise
+/// the calibration inherits the overconfidence of `model`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlattScaling<M> {
+    /// Model predicting the raw scores
+    pub model: M,
+    /// Maps raw scores to calibrated probabilities
+    pub calibrator: Logistic<f64>,
+}
+
+impl<M> PlattScaling<M>
+    where M: Model
+{
+    /// Wraps `model` with an identity-like calibration, which predicts `0.5` until calibrated
+    pub fn new(model: M) -> PlattScaling<M> {
+        PlattScaling {
+            model: model,
+            calibrator: Logistic::with_base_rate(0.0, 0.5),
+        }
+    }
+
+    /// Teaches the calibrator the events in `held_out` using `cost::MaxLikelihood`
+    ///
+    /// `model` itself is not changed. Truths must be `0` or `1`.
+    pub fn calibrate<T, H>(&mut self, teacher: &T, held_out: H)
+        where T: Teacher<Logistic<f64>>,
+              H: IntoIterator<Item = (M::Input, f64)>
+    {
+        let model = &self.model;
+        learn_history(teacher,
+                      &::cost::MaxLikelihood {},
+                      &mut self.calibrator,
+                      held_out.into_iter().map(|(input, truth)| (model.predict(&input), truth)));
+    }
+
+    /// Calibrated probability of the positive class for `input`
+    pub fn calibrated_predict(&self, input: &M::Input) -> f64 {
+        self.calibrator.predict(&self.model.predict(input))
+    }
+}
+
+/// Models with an exact closed form solution minimizing `cost::LeastSquares`
+///
+/// Provides an exact baseline, e.g. to validate the result of an iterative training.
+#[cfg(feature="std")]
+pub trait ClosedForm: Model + Sized {
+    /// Creates the model with the least squared error over `history`
+    fn fit_exact<H>(history: H) -> Self where H: IntoIterator<Item = (Self::Input, f64)>;
+}
+
+#[cfg(feature="std")]
+impl<I> ClosedForm for Constant<I> {
+    fn fit_exact<H>(history: H) -> Self
+        where H: IntoIterator<Item = (I, f64)>
+    {
+        Constant::fit_mean(history)
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> ClosedForm for Linear<V>
+    where V: Vector<Scalar = f64>
+{
+    /// Solves the normal equations `X^T X w = X^T y`, with a trailing column of ones in `X` for
+    /// the offset
+    ///
+    /// # Panics
+    ///
+    /// If `history` is empty or the normal equations are singular, e.g. because a feature is
+    /// constant.
+    fn fit_exact<H>(history: H) -> Self
+        where H: IntoIterator<Item = (V, f64)>
+    {
+        let history: Vec<_> = history.into_iter().collect();
+        let mut m = history.first().expect("history must not be empty").0.clone();
+        let n = m.dimension() + 1;
+        // Design matrix with a trailing column of ones for the offset
+        let row = |input: &V| -> Vec<f64> {
+            (0..n).map(|i| if i == n - 1 { 1.0 } else { input.at(i) }).collect()
+        };
+        let x: Vec<Vec<f64>> = history.iter().map(|&(ref input, _)| row(input)).collect();
+        let y: Vec<f64> = history.iter().map(|&(_, truth)| truth).collect();
+
+        let w = solve_normal_equations(&x, &y).expect("singular system of equations");
+        for i in 0..n - 1 {
+            *m.mut_at(i) = w[i];
+        }
+        Linear { m: m, c: w[n - 1] }
+    }
+}
+
+/// Models which can be rendered as a human readable equation
+///
+/// Helps to sanity check what a model has learned.
+#[cfg(feature="std")]
+pub trait Describe {
+    /// Renders the model as an equation, e.g. `y = 1.02*x0 + 1.98*x1 + 3.01`
+    ///
+    /// Coefficents are rounded to two decimals.
+    fn to_equation(&self) -> String;
+
+    /// Like `to_equation`, but the i-th feature is called `feature_names[i]` instead of `xi`
+    ///
+    /// Features without a name keep their default. Models without features ignore the names.
+    fn to_equation_named(&self, _feature_names: &[String]) -> String {
+        self.to_equation()
+    }
+
+    /// Attaches `feature_names` to the model, which are then used by `to_equation`
+    fn with_feature_names(self, feature_names: Vec<String>) -> Named<Self>
+        where Self: Sized
+    {
+        Named {
+            model: self,
+            feature_names: feature_names,
+        }
+    }
+}
+
+/// Model carrying the names of its features, e.g. for rendering it as an equation
+///
+/// Predicts and trains just like the wrapped `model`. Created by `Describe::with_feature_names`.
+#[cfg(feature="std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Named<M> {
+    /// Model doing the actual predictions
+    pub model: M,
+    /// Name of each feature in order
+    pub feature_names: Vec<String>,
+}
+
+#[cfg(feature="std")]
+impl<M> Model for Named<M>
+    where M: Model
+{
+    type Input = M::Input;
+
+    fn predict(&self, input: &M::Input) -> f64 {
+        self.model.predict(input)
+    }
+
+    fn num_coefficents(&self) -> usize {
+        self.model.num_coefficents()
+    }
+
+    fn gradient(&self, coefficent: usize, input: &M::Input) -> f64 {
+        self.model.gradient(coefficent, input)
+    }
+
+    fn coefficent(&mut self, coefficent: usize) -> &mut f64 {
+        self.model.coefficent(coefficent)
+    }
+
+    fn validate(&self, sample_input: &M::Input) -> Result<(), Error> {
+        self.model.validate(sample_input)
+    }
+}
+
+#[cfg(feature="std")]
+impl<M> Describe for Named<M>
+    where M: Describe
+{
+    fn to_equation(&self) -> String {
+        self.model.to_equation_named(&self.feature_names)
+    }
+}
+
+// Renders `m * x + c` as `m0*x0 + m1*x1 + ... + c`, using `feature_names` where available
+#[cfg(feature="std")]
+fn linear_terms<V>(linear: &Linear<V>, feature_names: &[String]) -> String
+    where V: Vector<Scalar = f64>
+{
+    let mut terms = String::new();
+    for i in 0..linear.m.dimension() {
+        let variable = match feature_names.get(i) {
+            Some(name) => format!("*{}", name),
+            None => format!("*x{}", i),
+        };
+        push_term(&mut terms, linear.m.at(i), &variable);
+    }
+    push_term(&mut terms, linear.c, "");
+    terms
+}
+
+// Appends `coefficent` times `variable` to `terms`, joined by the sign of `coefficent`
+#[cfg(feature="std")]
+fn push_term(terms: &mut String, coefficent: f64, variable: &str) {
+    if terms.is_empty() {
+        terms.push_str(&format!("{:.2}{}", coefficent, variable));
+    } else if coefficent < 0.0 {
+        terms.push_str(&format!(" - {:.2}{}", -coefficent, variable));
+    } else {
+        terms.push_str(&format!(" + {:.2}{}", coefficent, variable));
+    }
+}
+
+#[cfg(feature="std")]
+impl<I> Describe for Constant<I> {
+    fn to_equation(&self) -> String {
+        format!("y = {:.2}", self.c)
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> Describe for Linear<V>
+    where V: Vector<Scalar = f64>
+{
+    fn to_equation(&self) -> String {
+        self.to_equation_named(&[])
+    }
+
+    fn to_equation_named(&self, feature_names: &[String]) -> String {
+        format!("y = {}", linear_terms(self, feature_names))
+    }
+}
+
+#[cfg(feature="std")]
+impl<V> Describe for Logistic<V>
+    where V: Vector<Scalar = f64>
+{
+    fn to_equation(&self) -> String {
+        self.to_equation_named(&[])
+    }
+
+    fn to_equation_named(&self, feature_names: &[String]) -> String {
+        format!("y = 1/(1+e^({}))", linear_terms(&self.0, feature_names))
     }
 }
@@ -0,0 +1,366 @@
+use {Model, DynModel, Cost, Teacher, Training, learn_history, mean_cost};
+use model::{Linear, Stacking, GrowableModel};
+#[cfg(feature="rand")]
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+/// Trains a model for each penalty strength in `lambdas`
+///
+/// `teacher` creates a regularized teacher for a penalty strength, e.g. `teacher::Ridge`.
+/// Each model is trained for `epochs` passes over `history`, warm starting from the solution
+/// of the previous `lambda`. The first model starts from `start`. Returns every `lambda`
+/// together with its trained model, revealing how the coefficents shrink with growing penalty.
+pub fn regularization_path<M, C, T, F, Truth>(teacher: F,
+                                              cost: &C,
+                                              start: &M,
+                                              history: &[(M::Input, Truth)],
+                                              epochs: usize,
+                                              lambdas: &[f64])
+                                              -> Vec<(f64, M)>
+    where M: Model,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          F: Fn(f64) -> T,
+          Truth: Copy
+{
+    let mut model = start.clone();
+    lambdas.iter()
+        .map(|&lambda| {
+            let mut training = teacher(lambda).new_training(&model);
+            for _ in 0..epochs {
+                training.teach_batch(cost, &mut model, history);
+            }
+            (lambda, model.clone())
+        })
+        .collect()
+}
+
+/// Picks the batch size from `candidates` with the highest throughput for `teacher`
+///
+/// For each candidate, a copy of `model` is trained with one pass over `sample`, split into
+/// batches of that size and passed to `Training::teach_batch`. Returns the batch size of the
+/// fastest pass. Since every pass learns the same events, it also processes the most events
+/// per second. `sample` should be large enough for the timings to be meaningful.
+///
+/// # Panics
+///
+/// If `candidates` is empty or contains zero.
+pub fn tune_batch_size<M, C, T, Truth>(teacher: &T,
+                                       cost: &C,
+                                       model: &M,
+                                       sample: &[(M::Input, Truth)],
+                                       candidates: &[usize])
+                                       -> usize
+    where M: Model,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          Truth: Copy
+{
+    use std::time::Instant;
+
+    assert!(!candidates.is_empty(), "no candidate batch sizes");
+
+    let mut best = None;
+    for &batch_size in candidates {
+        let mut model = model.clone();
+        let mut training = teacher.new_training(&model);
+        let start = Instant::now();
+        for batch in sample.chunks(batch_size) {
+            training.teach_batch(cost, &mut model, batch);
+        }
+        let elapsed = start.elapsed();
+        best = match best {
+            Some((_, fastest)) if fastest <= elapsed => best,
+            _ => Some((batch_size, elapsed)),
+        };
+    }
+    best.unwrap().0
+}
+
+/// Shuffles `data` and splits it into a training and a test set
+///
+/// The test set contains `test_fraction` of the events, rounded to the nearest integer. The
+/// shuffle is determined by `seed`, which must not be all zeros.
+///
+/// # Panics
+///
+/// If `test_fraction` is not between 0 and 1.
+#[cfg(feature="rand")]
+pub fn train_test_split<T>(data: &[T], test_fraction: f64, seed: [u32; 4]) -> (Vec<T>, Vec<T>)
+    where T: Clone
+{
+    assert!(test_fraction >= 0.0 && test_fraction <= 1.0,
+            "test_fraction must be between 0 and 1");
+    let mut shuffled = data.to_vec();
+    XorShiftRng::from_seed(seed).shuffle(&mut shuffled);
+    let test_size = (test_fraction * data.len() as f64).round() as usize;
+    let test = shuffled.split_off(data.len() - test_size);
+    (shuffled, test)
+}
+
+/// Trains `n` models from random initializations and returns the one with the lowest cost
+///
+/// A simple global search for non-convex objectives, whose trainings may end up in a poor local
+/// minimum. `init` draws a starting model from the random number generator seeded with `seed`,
+/// which must not be all zeros. `train` trains a model and returns its final cost.
+///
+/// The first numbers drawn for small seeds like `[1, 2, 3, 4]` are very similar, so prefer seeds
+/// with many bits set.
+///
+/// # Panics
+///
+/// If `n` is zero.
+#[cfg(feature="rand")]
+pub fn multi_start<M, F, I>(train: F, init: I, n: usize, seed: [u32; 4]) -> M
+    where F: Fn(&mut M) -> f64,
+          I: Fn(&mut XorShiftRng) -> M
+{
+    assert!(n > 0, "multi_start requires at least one start");
+    let mut rng = XorShiftRng::from_seed(seed);
+    let mut best: Option<(M, f64)> = None;
+    for _ in 0..n {
+        let mut model = init(&mut rng);
+        let cost = train(&mut model);
+        best = match best {
+            Some((_, lowest)) if !(cost < lowest) => best,
+            _ => Some((model, cost)),
+        };
+    }
+    best.unwrap().0
+}
+
+// Splits `data` into `folds` contiguous parts and returns all but the `fold`-th part, together
+// with the `fold`-th part
+fn split_fold<T>(data: &[T], folds: usize, fold: usize) -> (Vec<T>, &[T])
+    where T: Clone
+{
+    let begin = fold * data.len() / folds;
+    let end = (fold + 1) * data.len() / folds;
+    let mut rest = data[..begin].to_vec();
+    rest.extend_from_slice(&data[end..]);
+    (rest, &data[begin..end])
+}
+
+/// Mean cost of models trained by `train` on held out parts of `data`
+///
+/// Splits `data` into `folds` contiguous parts. For each part a model is trained on all other
+/// parts and evaluated on the held out one. Returns the mean `cost` of all predictions for
+/// held out events. Shuffle `data` beforehand if its order is meaningful.
+///
+/// # Panics
+///
+/// If `folds` is less than two.
+pub fn cross_validate<M, C, F, Truth>(cost: &C,
+                                      data: &[(M::Input, Truth)],
+                                      folds: usize,
+                                      train: F)
+                                      -> f64
+    where M: Model,
+          M::Input: Clone,
+          C: Cost<Truth>,
+          F: Fn(&[(M::Input, Truth)]) -> M,
+          Truth: Copy
+{
+    assert!(folds >= 2, "cross validation requires at least two folds");
+    let mut total_cost = 0.0;
+    for fold in 0..folds {
+        let (training_set, held_out) = split_fold(data, folds, fold);
+        let model = train(&training_set);
+        total_cost += held_out.iter()
+            .map(|&(ref features, truth)| cost.cost(model.predict(features), truth))
+            .sum::<f64>();
+    }
+    total_cost / data.len() as f64
+}
+
+/// Returns the parameters in `param_grid` with the lowest cross validated cost, together with
+/// that cost
+///
+/// `param_grid` should contain every combination of the hyperparameters to try, e.g. tuples of
+/// learning rate and number of epochs. `train_fn` trains a model with the given parameters on
+/// the given events. Each parameter set is scored with `cross_validate`. With the `rayon`
+/// feature enabled, the parameter sets are evaluated in parallel.
+///
+/// # Panics
+///
+/// If `param_grid` is empty, or `folds` is less than two.
+pub fn grid_search<P, M, C, F, Truth>(param_grid: &[P],
+                                      cost: &C,
+                                      data: &[(M::Input, Truth)],
+                                      folds: usize,
+                                      train_fn: F)
+                                      -> (P, f64)
+    where P: Clone + Sync,
+          M: Model + Send,
+          M::Input: Clone + Send + Sync,
+          C: Cost<Truth> + Sync,
+          F: Fn(&P, &[(M::Input, Truth)]) -> M + Sync,
+          Truth: Copy + Send + Sync
+{
+    let score = |params: &P| cross_validate(cost, data, folds, |events| train_fn(params, events));
+
+    #[cfg(feature="rayon")]
+    let scores: Vec<f64> = {
+        use rayon::prelude::*;
+        param_grid.par_iter().map(score).collect()
+    };
+    #[cfg(not(feature="rayon"))]
+    let scores: Vec<f64> = param_grid.iter().map(score).collect();
+
+    param_grid.iter()
+        .zip(scores)
+        .fold(None,
+              |best: Option<(&P, f64)>, (params, score)| match best {
+                  Some((_, best_score)) if best_score <= score => best,
+                  _ => Some((params, score)),
+              })
+        .map(|(params, score)| (params.clone(), score))
+        .expect("param_grid must not be empty")
+}
+
+/// Trains the base models created by `base_trainers` and a `Linear` meta model combining their
+/// predictions
+///
+/// To avoid leaking the truth into the meta model, it is trained on out of fold predictions:
+/// `data` is split into `folds` parts and the prediction of a base model for an event in one
+/// part stems from a model trained on all other parts. The meta model is taught these
+/// predictions `epochs` times by `meta_teacher`. Finally the base models are trained on all of
+/// `data`.
+///
+/// # Panics
+///
+/// If `folds` is less than two.
+pub fn fit_stacking<I, C, T>(base_trainers: &[Box<dyn Fn(&[(I, f64)]) -> Box<dyn DynModel<I>>>],
+                             meta_teacher: &T,
+                             cost: &C,
+                             data: &[(I, f64)],
+                             folds: usize,
+                             epochs: usize)
+                             -> Stacking<I>
+    where I: Clone,
+          C: Cost<f64>,
+          T: Teacher<Linear<Vec<f64>>>
+{
+    assert!(folds >= 2, "stacking requires at least two folds");
+    let mut meta_history = Vec::with_capacity(data.len());
+    for fold in 0..folds {
+        let (training_set, held_out) = split_fold(data, folds, fold);
+        let base: Vec<_> = base_trainers.iter().map(|train| train(&training_set)).collect();
+        for &(ref input, truth) in held_out {
+            let predictions = base.iter().map(|m| m.predict_dyn(input)).collect::<Vec<f64>>();
+            meta_history.push((predictions, truth));
+        }
+    }
+
+    let mut meta = Linear {
+        m: vec![0.0; base_trainers.len()],
+        c: 0.0,
+    };
+    learn_history(meta_teacher,
+                  cost,
+                  &mut meta,
+                  meta_history.iter().cycle().take(epochs * meta_history.len()).cloned());
+
+    Stacking {
+        base: base_trainers.iter().map(|train| train(data)).collect(),
+        meta: meta,
+    }
+}
+
+/// Learning rate range test
+///
+/// Trains a copy of `model` for `steps` passes over `data`, multiplying the learning rate by a
+/// constant factor after each pass, so it grows exponentially from `min_rate` to `max_rate`.
+/// `teacher` creates a teacher for a learning rate. Returns each learning rate together with the
+/// mean cost over `data` after the pass with that rate. Stops early once the cost is no longer
+/// finite. A good learning rate lies where the cost decreases the steepest, before it diverges.
+pub fn lr_find<M, C, T, F, Truth>(teacher: F,
+                                  cost: &C,
+                                  model: &M,
+                                  data: &[(M::Input, Truth)],
+                                  min_rate: f64,
+                                  max_rate: f64,
+                                  steps: usize)
+                                  -> Vec<(f64, f64)>
+    where M: Model,
+          C: Cost<Truth>,
+          T: Teacher<M>,
+          F: Fn(f64) -> T,
+          Truth: Copy
+{
+    let mut model = model.clone();
+    let factor = (max_rate / min_rate).powf(1.0 / (steps as f64 - 1.0).max(1.0));
+    let mut curve = Vec::with_capacity(steps);
+    let mut rate = min_rate;
+    for _ in 0..steps {
+        teacher(rate).new_training(&model).teach_batch(cost, &mut model, data);
+        let loss = mean_cost(cost, &model, data);
+        curve.push((rate, loss));
+        if !loss.is_finite() {
+            break;
+        }
+        rate *= factor;
+    }
+    curve
+}
+
+/// Stepwise forward selection of the features of a `Linear` model
+///
+/// Starting with no features, repeatedly adds the feature which reduces the mean `cost` over
+/// `validation` the most. For each candidate, a copy of the current model is grown by
+/// `GrowableModel::push_coefficent` and trained `epochs` times over `training` by `teacher`.
+/// Stops once no candidate reduces the cost by more than `min_improvement`. Returns the indices
+/// of the selected features in order of selection, together with the model using them.
+pub fn forward_selection<C, T>(teacher: &T,
+                               cost: &C,
+                               training: &[(Vec<f64>, f64)],
+                               validation: &[(Vec<f64>, f64)],
+                               epochs: usize,
+                               min_improvement: f64)
+                               -> (Vec<usize>, Linear<Vec<f64>>)
+    where C: Cost<f64>,
+          T: Teacher<Linear<Vec<f64>>>
+{
+    let project = |events: &[(Vec<f64>, f64)], features: &[usize]| -> Vec<(Vec<f64>, f64)> {
+        events.iter()
+            .map(|&(ref x, y)| (features.iter().map(|&f| x[f]).collect(), y))
+            .collect()
+    };
+    let train = |mut model: Linear<Vec<f64>>, features: &[usize]| {
+        let events = project(training, features);
+        let mut state = teacher.new_training(&model);
+        for _ in 0..epochs {
+            state.teach_batch(cost, &mut model, &events);
+        }
+        let validation_cost = mean_cost(cost, &model, &project(validation, features));
+        (model, validation_cost)
+    };
+
+    let dimension = training.first().map_or(0, |&(ref x, _)| x.len());
+    let mut selected = Vec::new();
+    let (mut model, mut current_cost) = train(Linear { m: Vec::new(), c: 0.0 }, &selected);
+
+    loop {
+        let best = (0..dimension)
+            .filter(|f| !selected.contains(f))
+            .map(|f| {
+                let mut features = selected.clone();
+                features.push(f);
+                let mut candidate = model.clone();
+                candidate.push_coefficent(0.0);
+                (f, train(candidate, &features))
+            })
+            .fold(None,
+                  |best: Option<(usize, (Linear<Vec<f64>>, f64))>, candidate| match best {
+                      Some(ref b) if (b.1).1 <= (candidate.1).1 => best,
+                      _ => Some(candidate),
+                  });
+        match best {
+            Some((feature, (candidate, cost))) if current_cost - cost > min_improvement => {
+                selected.push(feature);
+                model = candidate;
+                current_cost = cost;
+            }
+            _ => return (selected, model),
+        }
+    }
+}
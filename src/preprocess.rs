@@ -0,0 +1,440 @@
+use {Model, Cost, Teacher, learn_history};
+use linear_algebra::Vector;
+
+// Mean and variance of each feature in `data`
+//
+// The variance of a feature is exactly zero if all its values are equal, even if rounding errors
+// of the mean would leave a tiny positive variance, e.g. for a constant `0.1`.
+fn moments<V>(data: &[V]) -> (Vec<f64>, Vec<f64>)
+    where V: Vector<Scalar = f64>
+{
+    let dimension = data.first().map_or(0, |v| v.dimension());
+    let n = data.len() as f64;
+    let mut mean = vec![0.0; dimension];
+    let mut variance = vec![0.0; dimension];
+    for v in data {
+        for i in 0..dimension {
+            mean[i] += v.at(i) / n;
+        }
+    }
+    for v in data {
+        for i in 0..dimension {
+            variance[i] += (v.at(i) - mean[i]).powi(2) / n;
+        }
+    }
+    if let Some(first) = data.first() {
+        for i in 0..dimension {
+            if data.iter().all(|v| v.at(i) == first.at(i)) {
+                mean[i] = first.at(i);
+                variance[i] = 0.0;
+            }
+        }
+    }
+    (mean, variance)
+}
+
+/// Indices of all features in `data` with zero variance
+///
+/// Such features carry no information, yet still consume a coefficent of a model using them.
+pub fn detect_constant_features<V>(data: &[V]) -> Vec<usize>
+    where V: Vector<Scalar = f64>
+{
+    let (_, variance) = moments(data);
+    variance.iter().enumerate().filter(|&(_, &v)| v == 0.0).map(|(i, _)| i).collect()
+}
+
+/// Scales each feature to zero mean and unit variance
+///
+/// Constant features are only centered, so they are mapped to zero instead of `NaN`.
+#[derive(Debug, Clone)]
+pub struct Standardizer {
+    /// Mean of each feature
+    pub mean: Vec<f64>,
+    /// Standard deviation of each feature. Set to `1` for constant features.
+    pub std_dev: Vec<f64>,
+}
+
+impl Standardizer {
+    /// Estimates mean and standard deviation of each feature in `data`
+    pub fn fit<V>(data: &[V]) -> Standardizer
+        where V: Vector<Scalar = f64>
+    {
+        let (mean, variance) = moments(data);
+        let std_dev = variance.iter()
+            .map(|&v| if v == 0.0 { 1.0 } else { v.sqrt() })
+            .collect();
+        Standardizer {
+            mean: mean,
+            std_dev: std_dev,
+        }
+    }
+
+    /// Standardizes the features of `input` in place
+    pub fn transform<V>(&self, input: &mut V)
+        where V: Vector<Scalar = f64>
+    {
+        for i in 0..input.dimension() {
+            *input.mut_at(i) = (input.at(i) - self.mean[i]) / self.std_dev[i];
+        }
+    }
+}
+
+/// Transformation of the features estimated from the training data
+///
+/// Used by `Pipeline` to apply the same transformation during training and prediction.
+pub trait Transform<V> {
+    /// Estimates the parameters of the transformation from `data`
+    fn fit(data: &[V]) -> Self;
+    /// Transforms `input` in place
+    fn transform(&self, input: &mut V);
+}
+
+impl<V> Transform<V> for Standardizer
+    where V: Vector<Scalar = f64>
+{
+    fn fit(data: &[V]) -> Standardizer {
+        Standardizer::fit(data)
+    }
+
+    fn transform(&self, input: &mut V) {
+        Standardizer::transform(self, input)
+    }
+}
+
+/// Clamps each feature to percentile bounds estimated from the training data (winsorizing)
+///
+/// Reduces the influence of extreme feature values on gradient based training. Percentiles
+/// interpolate linearly between the sorted values, so bounds are well defined for any number of
+/// data points. A single data point bounds each feature to its value.
+#[derive(Debug, Clone)]
+pub struct Winsorizer {
+    /// Percentile of the lower bound, between 0 and 100
+    pub lower_pct: f64,
+    /// Percentile of the upper bound, between 0 and 100
+    pub upper_pct: f64,
+    /// Lower bound of each feature
+    pub lower: Vec<f64>,
+    /// Upper bound of each feature
+    pub upper: Vec<f64>,
+}
+
+impl Winsorizer {
+    /// Estimates the `lower_pct`-th and `upper_pct`-th percentile of each feature in `data`
+    pub fn fit<V>(data: &[V], lower_pct: f64, upper_pct: f64) -> Winsorizer
+        where V: Vector<Scalar = f64>
+    {
+        assert!(0.0 <= lower_pct && lower_pct <= upper_pct && upper_pct <= 100.0,
+                "percentiles must satisfy 0 <= lower_pct <= upper_pct <= 100");
+        let dimension = data.first().map_or(0, |v| v.dimension());
+        let mut lower = Vec::with_capacity(dimension);
+        let mut upper = Vec::with_capacity(dimension);
+        for i in 0..dimension {
+            let mut values: Vec<f64> = data.iter().map(|v| v.at(i)).collect();
+            values.sort_by(|a, b| a.partial_cmp(b).expect("features must not be NaN"));
+            lower.push(percentile(&values, lower_pct));
+            upper.push(percentile(&values, upper_pct));
+        }
+        Winsorizer {
+            lower_pct: lower_pct,
+            upper_pct: upper_pct,
+            lower: lower,
+            upper: upper,
+        }
+    }
+
+    /// Clamps the features of `input` in place
+    pub fn transform<V>(&self, input: &mut V)
+        where V: Vector<Scalar = f64>
+    {
+        for i in 0..input.dimension() {
+            *input.mut_at(i) = input.at(i).max(self.lower[i]).min(self.upper[i]);
+        }
+    }
+}
+
+// `pct`-th percentile of the non empty, ascending `sorted`, interpolating between neighbours
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (rank - below as f64) * (sorted[above] - sorted[below])
+}
+
+/// Normalizes minibatches to zero mean and unit variance, in the style of batch normalization
+///
+/// During training each minibatch is standardized with its own statistics, which are also
+/// blended into running estimates. At prediction time `transform` uses the running estimates,
+/// so single events can be normalized.
+#[derive(Debug, Clone)]
+pub struct BatchNorm {
+    /// Weight of the previous running statistics when blending in a new batch
+    pub momentum: f64,
+    /// Added to the variance to avoid a division by zero
+    pub epsilon: f64,
+    /// Running estimate of the mean of each feature
+    pub running_mean: Vec<f64>,
+    /// Running estimate of the variance of each feature
+    pub running_variance: Vec<f64>,
+}
+
+impl BatchNorm {
+    /// Creates a normalization for features with `dimension` entries
+    ///
+    /// Running statistics start out at zero mean and unit variance.
+    pub fn new(dimension: usize, momentum: f64) -> BatchNorm {
+        BatchNorm {
+            momentum: momentum,
+            epsilon: 1e-5,
+            running_mean: vec![0.0; dimension],
+            running_variance: vec![1.0; dimension],
+        }
+    }
+
+    /// Normalizes `batch` in place with its own statistics and updates the running estimates
+    ///
+    /// Returns mean and variance of the batch.
+    pub fn normalize_batch<V>(&mut self, batch: &mut [V]) -> (Vec<f64>, Vec<f64>)
+        where V: Vector<Scalar = f64>
+    {
+        let (mean, variance) = moments(batch);
+        for v in batch.iter_mut() {
+            for i in 0..mean.len() {
+                *v.mut_at(i) = (v.at(i) - mean[i]) / (variance[i] + self.epsilon).sqrt();
+            }
+        }
+        for i in 0..mean.len() {
+            self.running_mean[i] = self.momentum * self.running_mean[i] +
+                                   (1.0 - self.momentum) * mean[i];
+            self.running_variance[i] = self.momentum * self.running_variance[i] +
+                                       (1.0 - self.momentum) * variance[i];
+        }
+        (mean, variance)
+    }
+
+    /// Normalizes `input` in place using the running estimates
+    pub fn transform<V>(&self, input: &mut V)
+        where V: Vector<Scalar = f64>
+    {
+        for i in 0..input.dimension() {
+            *input.mut_at(i) = (input.at(i) - self.running_mean[i]) /
+                               (self.running_variance[i] + self.epsilon).sqrt();
+        }
+    }
+}
+
+/// Maps string features into a fixed number of buckets (hashing trick)
+///
+/// No vocabulary needs to be stored. Different features may share a bucket, which is accepted
+/// by design. The FNV-1a hash is used, so buckets are stable across platforms and builds.
+#[derive(Debug, Clone, Copy)]
+pub struct HashingEncoder {
+    /// Number of buckets, i.e. the dimension of the encoded features
+    pub buckets: usize,
+}
+
+impl HashingEncoder {
+    /// Bucket of `feature` in `0..buckets`
+    pub fn bucket(&self, feature: &str) -> usize {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in feature.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash % self.buckets as u64) as usize
+    }
+
+    /// Sparse representation of `features` as pairs of bucket and count, sorted by bucket
+    pub fn encode<'a, I>(&self, features: I) -> Vec<(usize, f64)>
+        where I: IntoIterator<Item = &'a str>
+    {
+        let mut buckets: Vec<usize> = features.into_iter().map(|f| self.bucket(f)).collect();
+        buckets.sort();
+        let mut encoded: Vec<(usize, f64)> = Vec::new();
+        for bucket in buckets {
+            match encoded.last_mut() {
+                Some(&mut (last, ref mut count)) if last == bucket => *count += 1.0,
+                _ => encoded.push((bucket, 1.0)),
+            }
+        }
+        encoded
+    }
+}
+
+/// Chains a `Transform` of the features, a model and a teacher
+///
+/// `fit` estimates the transformation from the training data and teaches the model the
+/// transformed events. `predict` applies the same transformation to the input, so it can not be
+/// forgotten at inference.
+pub struct Pipeline<P, M, T> {
+    /// Transformation estimated by the last call to `fit`
+    pub transform: Option<P>,
+    /// Model trained on the transformed features
+    pub model: M,
+    /// Teaches `model`
+    pub teacher: T,
+    /// Number of passes over the data in `fit`
+    pub epochs: usize,
+}
+
+impl<P, M, T> Pipeline<P, M, T>
+    where P: Transform<M::Input>,
+          M: Model,
+          M::Input: Clone,
+          T: Teacher<M>
+{
+    /// Creates a pipeline which is yet to be fit
+    pub fn new(model: M, teacher: T, epochs: usize) -> Pipeline<P, M, T> {
+        Pipeline {
+            transform: None,
+            model: model,
+            teacher: teacher,
+            epochs: epochs,
+        }
+    }
+
+    /// Estimates the transformation from `data` and teaches `model` the transformed events
+    pub fn fit<C>(&mut self, cost: &C, data: &[(M::Input, f64)])
+        where C: Cost<f64>
+    {
+        let inputs: Vec<_> = data.iter().map(|&(ref input, _)| input.clone()).collect();
+        let transform = P::fit(&inputs);
+        let transformed: Vec<_> = data.iter()
+            .map(|&(ref input, truth)| {
+                let mut input = input.clone();
+                transform.transform(&mut input);
+                (input, truth)
+            })
+            .collect();
+        let events = transformed.len() * self.epochs;
+        learn_history(&self.teacher,
+                      cost,
+                      &mut self.model,
+                      transformed.iter().cycle().take(events).cloned());
+        self.transform = Some(transform);
+    }
+
+    /// Prediction of `model` for the transformed `input`
+    ///
+    /// `input` is passed unchanged if the pipeline has not been fit yet.
+    pub fn predict(&self, input: &M::Input) -> f64 {
+        match self.transform {
+            Some(ref transform) => {
+                let mut input = input.clone();
+                transform.transform(&mut input);
+                self.model.predict(&input)
+            }
+            None => self.model.predict(input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{detect_constant_features, Standardizer, HashingEncoder, BatchNorm, Winsorizer};
+
+    #[test]
+    fn constant_feature() {
+
+        let mut data = vec![[1.0, 5.0, 2.0], [3.0, 5.0, 4.0], [5.0, 5.0, 0.0]];
+
+        assert_eq!(vec![1], detect_constant_features(&data));
+
+        let standardizer = Standardizer::fit(&data);
+        for v in &mut data {
+            standardizer.transform(v);
+            assert!(v.iter().all(|x| x.is_finite()));
+            assert_eq!(0.0, v[1]);
+        }
+    }
+
+    #[test]
+    fn constant_feature_not_representable() {
+
+        // 0.1 has no exact binary representation, so its running mean is off by rounding errors
+        let mut data = vec![[0.1, 1.0]; 10];
+        data[3][1] = 2.0;
+
+        assert_eq!(vec![0], detect_constant_features(&data));
+
+        let standardizer = Standardizer::fit(&data);
+        assert_eq!(1.0, standardizer.std_dev[0]);
+        for v in &mut data {
+            standardizer.transform(v);
+            assert_eq!(0.0, v[0]);
+        }
+    }
+
+    #[test]
+    fn hashing_encoder() {
+
+        let encoder = HashingEncoder { buckets: 16 };
+        let words = ["the", "quick", "brown", "fox", "jumps", "over", "the", "lazy", "dog"];
+
+        assert_eq!(encoder.bucket("fox"), encoder.bucket("fox"));
+        assert!(words.iter().all(|w| encoder.bucket(w) < 16));
+
+        let encoded = encoder.encode(words.iter().cloned());
+        assert!(encoded.iter().all(|&(bucket, _)| bucket < 16));
+        assert_eq!(words.len() as f64, encoded.iter().map(|&(_, count)| count).sum::<f64>());
+        assert!(encoded.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn batch_norm_running_statistics() {
+
+        let mut norm = BatchNorm::new(1, 0.9);
+        // Mean 5, variance 2
+        let data: Vec<[f64; 1]> = (0..2000).map(|i| [5.0 + 2.0 * (i as f64).sin()]).collect();
+
+        let mut first = None;
+        for batch in data.chunks(10) {
+            let mut batch = batch.to_vec();
+            let (mean, _) = norm.normalize_batch(&mut batch);
+            assert!(batch.iter().all(|v| v[0].abs() < 10.0));
+            first = first.or(Some((mean[0], norm.running_mean[0])));
+        }
+
+        // Batch statistics differ from the running ones in the beginning ...
+        let (first_batch_mean, first_running_mean) = first.unwrap();
+        assert!((first_batch_mean - first_running_mean).abs() > 1.0);
+        // ... but the running ones converge towards the statistics of the whole data
+        assert!((norm.running_mean[0] - 5.0).abs() < 0.2);
+        assert!((norm.running_variance[0] - 2.0).abs() < 0.5);
+
+        let mut input = [5.0];
+        norm.transform(&mut input);
+        assert!(input[0].abs() < 0.2);
+    }
+
+    #[test]
+    fn winsorizer_clamps_outliers() {
+
+        // First feature 0, 1, ..., 100, the second one has a single extreme outlier
+        let data: Vec<[f64; 2]> =
+            (0..101).map(|i| [i as f64, if i == 50 { 1e6 } else { 1.0 }]).collect();
+        let winsorizer = Winsorizer::fit(&data, 5.0, 95.0);
+        assert_eq!(vec![5.0, 1.0], winsorizer.lower);
+        assert_eq!(vec![95.0, 1.0], winsorizer.upper);
+
+        let mut below = [-20.0, 1e6];
+        winsorizer.transform(&mut below);
+        assert_eq!([5.0, 1.0], below);
+
+        let mut above = [120.0, -3.0];
+        winsorizer.transform(&mut above);
+        assert_eq!([95.0, 1.0], above);
+
+        let mut interior = [42.5, 1.0];
+        winsorizer.transform(&mut interior);
+        assert_eq!([42.5, 1.0], interior);
+
+        // Fewer data points than the percentiles resolve interpolate between them
+        let winsorizer = Winsorizer::fit(&[[0.0], [10.0]], 5.0, 95.0);
+        assert_eq!(vec![0.5], winsorizer.lower);
+        assert_eq!(vec![9.5], winsorizer.upper);
+        let winsorizer = Winsorizer::fit(&[[3.0]], 5.0, 95.0);
+        assert_eq!(vec![3.0], winsorizer.lower);
+        assert_eq!(vec![3.0], winsorizer.upper);
+    }
+}
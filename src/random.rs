@@ -0,0 +1,41 @@
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+/// Single source of randomness for a whole training run
+///
+/// Seed it once and pass it to every randomized operation, so the run is reproducible from that
+/// seed alone. It implements `Rng`, so it can be used directly wherever vikos accepts a random
+/// number generator, e.g. for `Logistic::sample`, `DecisionStump::fit_random_subset`, shuffling
+/// (`Rng::shuffle`) or drawing initial coefficents. Randomized teachers like
+/// `teacher::GradientDescentDropout` take a seed instead, which is drawn with `next_seed`.
+///
+/// Every operation consumes numbers from the same stream, in the order the operations are
+/// called. A run is therefore only reproducible if it performs the same operations in the same
+/// order, e.g. initialization, then shuffling, then seeding the teacher. Trainings created from a
+/// drawn seed own their generator and do not consume numbers from the stream while learning.
+pub struct TrainingRng {
+    rng: XorShiftRng,
+}
+
+impl TrainingRng {
+    /// Creates the generator for a training run, `seed` must not be all zeros
+    pub fn new(seed: [u32; 4]) -> TrainingRng {
+        TrainingRng { rng: XorShiftRng::from_seed(seed) }
+    }
+
+    /// Draws a seed for a component owning its own generator, e.g. a randomized teacher
+    ///
+    /// The seed is never all zeros.
+    pub fn next_seed(&mut self) -> [u32; 4] {
+        let mut seed = [self.next_u32(), self.next_u32(), self.next_u32(), self.next_u32()];
+        if seed == [0; 4] {
+            seed[0] = 1;
+        }
+        seed
+    }
+}
+
+impl Rng for TrainingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+}
@@ -1,7 +1,8 @@
 use ::training;
-use Teacher;
-use Model;
+use {Teacher, Model, Cost, learn_history};
 use std::marker::PhantomData;
+#[cfg(feature="rand")]
+use rand::{SeedableRng, XorShiftRng};
 
 /// Gradient descent
 ///
@@ -24,6 +25,503 @@ impl<M> Teacher<M> for GradientDescent
     }
 }
 
+/// Gradient descent with fixed learning rate, randomly skipping coefficent updates
+///
+/// Each coefficent update is skipped with a probability of `dropout`. Trainings are
+/// deterministic for a given `seed`.
+#[cfg(feature="rand")]
+pub struct GradientDescentDropout {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Probability of skipping the update of a coefficent, between 0 and 1
+    pub dropout: f64,
+    /// Seed of the random number generator deciding which updates are skipped
+    ///
+    /// Must not be all zeros
+    pub seed: [u32; 4],
+}
+
+#[cfg(feature="rand")]
+impl<M> Teacher<M> for GradientDescentDropout
+    where M: Model
+{
+    type Training = training::GradientDescentDropout<M>;
+
+    fn new_training(&self, _: &M) -> training::GradientDescentDropout<M> {
+        training::GradientDescentDropout {
+            learning_rate: self.learning_rate,
+            dropout: self.dropout,
+            rng: XorShiftRng::from_seed(self.seed),
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent with decaying gaussian noise added to the gradients
+///
+/// See `training::GradientNoise` for the annealing schedule. Trainings are deterministic for a
+/// given `seed`.
+#[cfg(feature="rand")]
+pub struct GradientNoise {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Standard deviation of the noise for the first event
+    pub noise_scale: f64,
+    /// Exponent of the decay of the noise variance, e.g. `0.55`
+    pub decay: f64,
+    /// Seed of the random number generator drawing the noise
+    ///
+    /// Must not be all zeros
+    pub seed: [u32; 4],
+}
+
+#[cfg(feature="rand")]
+impl<M> Teacher<M> for GradientNoise
+    where M: Model
+{
+    type Training = training::GradientNoise<M>;
+
+    fn new_training(&self, _: &M) -> training::GradientNoise<M> {
+        training::GradientNoise {
+            learning_rate: self.learning_rate,
+            noise_scale: self.noise_scale,
+            decay: self.decay,
+            rng: XorShiftRng::from_seed(self.seed),
+            learned_events: 0,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent sampling the events of a batch proportionally to their cost
+///
+/// Use `Training::teach_batch` to benefit from the importance sampling. Trainings are
+/// deterministic for a given `seed`.
+#[cfg(feature="rand")]
+pub struct ImportanceSgd {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Seed of the random number generator drawing the events
+    ///
+    /// Must not be all zeros
+    pub seed: [u32; 4],
+}
+
+#[cfg(feature="rand")]
+impl<M> Teacher<M> for ImportanceSgd
+    where M: Model
+{
+    type Training = training::ImportanceSgd<M>;
+
+    fn new_training(&self, _: &M) -> training::ImportanceSgd<M> {
+        training::ImportanceSgd {
+            learning_rate: self.learning_rate,
+            rng: XorShiftRng::from_seed(self.seed),
+            costs: Vec::new(),
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Records statistics about the gradients of each coefficent during trainings of `teacher`
+///
+/// See `training::GradientMonitor::summary`.
+pub struct GradientMonitor<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+}
+
+impl<M, T> Teacher<M> for GradientMonitor<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::GradientMonitor<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::GradientMonitor<T::Training> {
+        training::GradientMonitor {
+            training: self.teacher.new_training(model),
+            statistics: vec![training::GradientStatistics::new(); model.num_coefficents()],
+        }
+    }
+}
+
+/// Teaches the first `events` events using `first`, and all later ones using `second`
+///
+/// Allows staged trainings, e.g. momentum based gradient descent to explore, followed by plain
+/// gradient descent to fine tune the coefficents. The training state of `second` is created
+/// along with the one of `first`. The switch is counted in events, so to switch after `n` epochs
+/// over a history, pass `n` times the length of the history.
+pub struct Then<A, B> {
+    /// Teacher used in the beginning
+    pub first: A,
+    /// Teacher used after `events` events
+    pub second: B,
+    /// Number of events taught by `first`
+    pub events: usize,
+}
+
+impl<M, A, B> Teacher<M> for Then<A, B>
+    where M: Model,
+          A: Teacher<M>,
+          B: Teacher<M>
+{
+    type Training = training::Then<A::Training, B::Training>;
+
+    fn new_training(&self, model: &M) -> training::Then<A::Training, B::Training> {
+        training::Then {
+            first: self.first.new_training(model),
+            second: self.second.new_training(model),
+            remaining_events: self.events,
+        }
+    }
+}
+
+/// Skips events with a residual larger than `k` robust standard deviations, passing all others
+/// to `teacher`
+///
+/// A simple robust training strategy, independent of the cost function. See
+/// `training::RejectOutliers` for how the robust standard deviation is estimated.
+pub struct RejectOutliers<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Events with a residual larger than `k` robust standard deviations are skipped
+    pub k: f64,
+    /// Number of events taught before the filter is active, must be at least one
+    pub warmup: usize,
+    /// Added to the estimated robust standard deviation, so a median absolute residual of zero
+    /// does not reject every event. About `1e-3` times the typical magnitude of the truths is a
+    /// good start, e.g. `0.01` for truths around `10`. Only use `0` if the truths are noisy.
+    pub floor: f64,
+}
+
+impl<M, T> Teacher<M> for RejectOutliers<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::RejectOutliers<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::RejectOutliers<T::Training> {
+        assert!(self.warmup >= 1, "warmup must be at least one event");
+        training::RejectOutliers {
+            training: self.teacher.new_training(model),
+            k: self.k,
+            warmup: self.warmup,
+            floor: self.floor,
+            warmup_residuals: Vec::with_capacity(self.warmup),
+            median_absolute_residual: 0.0,
+            skipped: 0,
+        }
+    }
+}
+
+/// Divides the gradients of the trainings created by `teacher` by the local noise
+///
+/// See `training::RobustScaled` for how the noise is estimated from the last `window` events.
+pub struct RobustScaled<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Number of recent events the noise is estimated from, must be at least one
+    pub window: usize,
+    /// Lower bound of the noise estimate
+    pub noise_floor: f64,
+}
+
+impl<M, T> Teacher<M> for RobustScaled<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::RobustScaled<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::RobustScaled<T::Training> {
+        assert!(self.window >= 1, "window must contain at least one event");
+        training::RobustScaled {
+            training: self.teacher.new_training(model),
+            window: self.window,
+            noise_floor: self.noise_floor,
+            residuals: Vec::with_capacity(self.window),
+            position: 0,
+        }
+    }
+}
+
+/// Averages the coefficents trained by `teacher` with exponentially decaying weights
+///
+/// See `training::Ema`. The average starts at the coefficents of the model the training is
+/// created for. Retrieve it with `training::Ema::ema_model`.
+pub struct Ema<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Weight of the previous average, between 0 and 1, e.g. `0.99`
+    pub decay: f64,
+}
+
+impl<M, T> Teacher<M> for Ema<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::Ema<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::Ema<T::Training> {
+        training::Ema {
+            training: self.teacher.new_training(model),
+            decay: self.decay,
+            average: model.clone(),
+        }
+    }
+}
+
+/// Projects the coefficents into a box after each event taught by `teacher`
+///
+/// Every coefficent `ci` is clamped into `[lower[ci], upper[ci]]`. Use `0` and `f64::INFINITY`
+/// for non negative coefficents, e.g. for non negative least squares. Both bounds must contain
+/// one entry for each coefficent of the model, including its offset.
+pub struct Constrained<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Lower bound of each coefficent
+    pub lower: Vec<f64>,
+    /// Upper bound of each coefficent
+    pub upper: Vec<f64>,
+}
+
+impl<M, T> Teacher<M> for Constrained<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::Constrained<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::Constrained<T::Training> {
+        assert!(self.lower.len() == model.num_coefficents() &&
+                self.upper.len() == model.num_coefficents(),
+                "there must be one lower and one upper bound for each of the {} coefficents, got \
+                 {} lower and {} upper bounds",
+                model.num_coefficents(),
+                self.lower.len(),
+                self.upper.len());
+        for ci in 0..self.lower.len() {
+            assert!(self.lower[ci] <= self.upper[ci],
+                    "lower bound {} of coefficent {} exceeds its upper bound {}",
+                    self.lower[ci],
+                    ci,
+                    self.upper[ci]);
+        }
+        training::Constrained {
+            training: self.teacher.new_training(model),
+            lower: self.lower.clone(),
+            upper: self.upper.clone(),
+        }
+    }
+}
+
+/// Trains only the coefficents not listed in `frozen`, e.g. to fine tune part of a model
+///
+/// See `training::FreezeCoefficents`. Indices count the offset as well, e.g. the offset of a
+/// `Linear` model with two features is coefficent `2`.
+pub struct FreezeCoefficents<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Indices of the coefficents which must not change
+    pub frozen: Vec<usize>,
+}
+
+impl<M, T> Teacher<M> for FreezeCoefficents<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::FreezeCoefficents<T::Training>;
+
+    /// # Panics
+    ///
+    /// If any frozen index is not smaller than the number of coefficents of `model`.
+    fn new_training(&self, model: &M) -> training::FreezeCoefficents<T::Training> {
+        let n = model.num_coefficents();
+        for &ci in &self.frozen {
+            assert!(ci < n,
+                    "frozen coefficent {} out of range, model has {} coefficents",
+                    ci,
+                    n);
+        }
+        training::FreezeCoefficents {
+            training: self.teacher.new_training(model),
+            frozen: self.frozen.clone(),
+        }
+    }
+}
+
+/// Rescales the gradient of every event to an L2 norm of at most `max_norm` before it is taught
+/// by `teacher`
+///
+/// In contrast to clipping each coefficent on its own, the direction of the gradient is kept.
+pub struct ClipGradientNorm<T> {
+    /// Teacher creating the trainings which change the coefficents
+    pub teacher: T,
+    /// Largest norm of the gradient of a single event
+    pub max_norm: f64,
+}
+
+impl<M, T> Teacher<M> for ClipGradientNorm<T>
+    where M: Model,
+          T: Teacher<M>
+{
+    type Training = training::ClipGradientNorm<T::Training>;
+
+    fn new_training(&self, model: &M) -> training::ClipGradientNorm<T::Training> {
+        training::ClipGradientNorm {
+            training: self.teacher.new_training(model),
+            max_norm: self.max_norm,
+            clipped: 0,
+        }
+    }
+}
+
+/// Gradient descent scaled by an estimate of the diagonal of the Hessian
+///
+/// Speeds up the training of ill conditioned problems, e.g. with features of very different
+/// scales. See `training::AdaHessian` for details and the tradeoff of `probe_interval`.
+#[cfg(feature="rand")]
+pub struct AdaHessian {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Weight of the previous average of the Hessian estimates, between 0 and 1
+    pub decay: f64,
+    /// Added to the denominator to avoid divisions by zero
+    pub epsilon: f64,
+    /// Number of events between two estimations of the Hessian, at least one
+    pub probe_interval: usize,
+    /// Seed of the random number generator drawing the probe vectors
+    ///
+    /// Must not be all zeros
+    pub seed: [u32; 4],
+}
+
+#[cfg(feature="rand")]
+impl<M> Teacher<M> for AdaHessian
+    where M: Model
+{
+    type Training = training::AdaHessian<M>;
+
+    fn new_training(&self, model: &M) -> training::AdaHessian<M> {
+        assert!(self.probe_interval >= 1, "probe_interval must be at least one");
+        training::AdaHessian {
+            learning_rate: self.learning_rate,
+            decay: self.decay,
+            epsilon: self.epsilon,
+            probe_interval: self.probe_interval,
+            rng: XorShiftRng::from_seed(self.seed),
+            hessian: vec![0.0; model.num_coefficents()],
+            last_update: vec![0.0; model.num_coefficents()],
+            probes: 0,
+            learned_events: 0,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent with fixed learning rate and ridge (L2) regularization
+///
+/// Minimizes the cost plus `lambda` times the sum of the squared coefficents. Every coefficent
+/// is penalized, including offsets like `Linear::c`.
+pub struct Ridge {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Strength of the penalty
+    pub lambda: f64,
+}
+
+impl<M> Teacher<M> for Ridge
+    where M: Model
+{
+    type Training = training::Ridge<M>;
+
+    fn new_training(&self, _: &M) -> training::Ridge<M> {
+        training::Ridge {
+            learning_rate: self.learning_rate,
+            lambda: self.lambda,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Stochastic gradient descent with truncated gradient L1 regularization
+///
+/// Following Langford, Li and Zhang, the L1 penalty is applied lazily every `period` events,
+/// truncating small coefficents to exactly zero. Use `f64::INFINITY` for `theta` to truncate
+/// all coefficents. Note that the bias of a model is a coefficent, too.
+pub struct TruncatedGradient {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Strength of the L1 penalty
+    pub gravity: f64,
+    /// Only coefficents with an absolute value of at most `theta` are truncated
+    pub theta: f64,
+    /// Number of events between two truncations, at least one
+    pub period: usize,
+}
+
+impl<M> Teacher<M> for TruncatedGradient
+    where M: Model
+{
+    type Training = training::TruncatedGradient<M>;
+
+    fn new_training(&self, _: &M) -> training::TruncatedGradient<M> {
+        assert!(self.period >= 1, "period must be at least one");
+        training::TruncatedGradient {
+            learning_rate: self.learning_rate,
+            gravity: self.gravity,
+            theta: self.theta,
+            period: self.period,
+            learned_events: 0,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Limited-memory BFGS, a quasi-Newton method minimizing the mean cost of a batch
+///
+/// Pass the whole history to `Training::teach_batch` for each step, e.g. using `learn_epochs`.
+/// See `training::Lbfgs`.
+pub struct Lbfgs {
+    /// Number of recent steps used to approximate the inverse Hessian, e.g. `10`. With `0` every
+    /// step follows the gradient.
+    pub memory: usize,
+}
+
+impl<M> Teacher<M> for Lbfgs
+    where M: Model
+{
+    type Training = training::Lbfgs<M>;
+
+    fn new_training(&self, _: &M) -> training::Lbfgs<M> {
+        training::Lbfgs {
+            memory: self.memory,
+            coefficent_changes: Vec::with_capacity(self.memory),
+            gradient_changes: Vec::with_capacity(self.memory),
+            previous: None,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Resilient backpropagation, a batch method which does not require a learning rate
+///
+/// Pass the whole history to `Training::teach_batch` for each step. See `training::Rprop`.
+pub struct Rprop;
+
+impl<M> Teacher<M> for Rprop
+    where M: Model
+{
+    type Training = training::Rprop<M>;
+
+    fn new_training(&self, model: &M) -> training::Rprop<M> {
+        let n = model.num_coefficents();
+        training::Rprop {
+            step_sizes: vec![training::Rprop::<M>::INITIAL_STEP; n],
+            previous_gradient: vec![0.0; n],
+            model_type: PhantomData {},
+        }
+    }
+}
+
 /// Gradient descent with annealing learning rate
 ///
 /// For the i-th event the learning rate is `l = l0 * (1 + i/t)`
@@ -53,6 +551,154 @@ impl<M> Teacher<M> for GradientDescentAl
     }
 }
 
+/// Gradient descent updating the coefficents once per batch
+///
+/// See `training::BatchGradientDescent`. Use `Reduction::Mean` (the default) to keep the
+/// meaning of `learning_rate` independent of the batch size.
+pub struct BatchGradientDescent {
+    /// Defines how fast the coefficents of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Whether the gradients of a batch are summed or averaged
+    pub reduction: training::Reduction,
+}
+
+impl<M> Teacher<M> for BatchGradientDescent
+    where M: Model
+{
+    type Training = training::BatchGradientDescent<M>;
+
+    fn new_training(&self, _: &M) -> training::BatchGradientDescent<M> {
+        training::BatchGradientDescent {
+            learning_rate: self.learning_rate,
+            reduction: self.reduction,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Batch gradient descent which speeds up while the cost decreases and backs off once it rises
+///
+/// See `training::BoldDriver`. Starting from a too small `learning_rate` is cheap, since it
+/// grows by `increase` with every successful epoch.
+pub struct BoldDriver {
+    /// Initial learning rate
+    pub learning_rate: f64,
+    /// Growth of the learning rate after an epoch which decreased the cost, e.g. `1.05`
+    pub increase: f64,
+    /// Shrinkage of the learning rate after an epoch which increased the cost, e.g. `0.5`
+    pub decrease: f64,
+    /// Lower bound of the learning rate
+    pub min_learning_rate: f64,
+    /// Upper bound of the learning rate
+    pub max_learning_rate: f64,
+}
+
+impl<M> Teacher<M> for BoldDriver
+    where M: Model
+{
+    type Training = training::BoldDriver<M>;
+
+    fn new_training(&self, _: &M) -> training::BoldDriver<M> {
+        training::BoldDriver {
+            learning_rate: self.learning_rate,
+            increase: self.increase,
+            decrease: self.decrease,
+            min_learning_rate: self.min_learning_rate,
+            max_learning_rate: self.max_learning_rate,
+            previous_cost: None,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent stepping along the direction of the gradient only
+///
+/// See `training::NormalizedGradientDescent`.
+pub struct NormalizedGradientDescent {
+    /// Length of each step in coefficent space
+    pub learning_rate: f64,
+}
+
+impl<M> Teacher<M> for NormalizedGradientDescent
+    where M: Model
+{
+    type Training = training::NormalizedGradientDescent<M>;
+
+    fn new_training(&self, _: &M) -> training::NormalizedGradientDescent<M> {
+        training::NormalizedGradientDescent {
+            learning_rate: self.learning_rate,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent with a cyclical learning rate (triangular policy)
+///
+/// The learning rate ramps linearly from `base` up to `max` during `step_size` events and back
+/// down to `base` during the following `step_size` events, repeating every `2 * step_size`
+/// events. The periodic rewarming helps to escape saddle points.
+pub struct CyclicalLr {
+    /// Lowest learning rate, used for the first event
+    pub base: f64,
+    /// Highest learning rate, reached after `step_size` events
+    pub max: f64,
+    /// Number of events for a ramp from `base` to `max`, half a cycle
+    pub step_size: f64,
+}
+
+impl<M> Teacher<M> for CyclicalLr
+    where M: Model
+{
+    type Training = training::CyclicalLr<M>;
+
+    fn new_training(&self, _: &M) -> training::CyclicalLr<M> {
+        training::CyclicalLr {
+            base: self.base,
+            max: self.max,
+            step_size: self.step_size,
+            learned_events: 0.0,
+            model_type: PhantomData {},
+        }
+    }
+}
+
+/// Gradient descent with warm restarts (SGDR)
+///
+/// The learning rate is cosine annealed from `lr_max` down to `lr_min` and then reset to
+/// `lr_max`. The first restart happens after `t0` events, each following period is `t_mult`
+/// times longer. The restarts help to escape poor local minima.
+pub struct Sgdr {
+    /// Number of events until the first restart, must be positive
+    pub t0: f64,
+    /// Growth factor of the period after each restart, e.g. `2.0`, must be at least one
+    pub t_mult: f64,
+    /// Learning rate right after each restart
+    pub lr_max: f64,
+    /// Learning rate approached right before each restart
+    pub lr_min: f64,
+}
+
+impl<M> Teacher<M> for Sgdr
+    where M: Model
+{
+    type Training = training::Sgdr<M>;
+
+    fn new_training(&self, _: &M) -> training::Sgdr<M> {
+        assert!(self.t0 > 0.0 && self.t_mult >= 1.0,
+                "Sgdr requires t0 > 0 and t_mult >= 1, got t0 = {} and t_mult = {}",
+                self.t0,
+                self.t_mult);
+        training::Sgdr {
+            t0: self.t0,
+            t_mult: self.t_mult,
+            lr_max: self.lr_max,
+            lr_min: self.lr_min,
+            learned_events: 0.0,
+            model_type: PhantomData {},
+        }
+    }
+}
+
 /// Gradient descent with annealing learning rate and momentum
 ///
 /// For the i-th event the learning rate is `l = l0 * (1 + i/t)`
@@ -90,6 +736,86 @@ impl<M> Teacher<M> for Momentum
     }
 }
 
+/// Named parameters for training a model with `Momentum`
+///
+/// Spares callers from confusing the learning rate with the inertia.
+///
+/// ```
+/// use vikos::{cost, model, teacher};
+///
+/// let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0)];
+/// let config = teacher::InertSgdConfig { inertia: 0.5, ..Default::default() };
+/// let model = config.run(&cost::LeastSquares {},
+///                        model::Linear { m: 0.0, c: 0.0 },
+///                        history.iter().cycle().take(300).cloned());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct InertSgdConfig {
+    /// Start learning rate
+    pub learning_rate: f64,
+    /// After `t` events the learning rate will be a half `learning_rate`
+    pub t: f64,
+    /// To simulate friction, please select a value smaller than 1 (recommended)
+    pub inertia: f64,
+}
+
+impl Default for InertSgdConfig {
+    fn default() -> InertSgdConfig {
+        InertSgdConfig {
+            learning_rate: 0.01,
+            t: 1000.0,
+            inertia: 0.9,
+        }
+    }
+}
+
+impl InertSgdConfig {
+    /// Teaches `start` all events in `history` and returns the trained model
+    pub fn run<M, C, H, Truth>(&self, cost: &C, start: M, history: H) -> M
+        where M: Model,
+              C: Cost<Truth>,
+              H: IntoIterator<Item = (M::Input, Truth)>,
+              Truth: Copy
+    {
+        let teacher = Momentum {
+            l0: self.learning_rate,
+            t: self.t,
+            inertia: self.inertia,
+        };
+        let mut model = start;
+        learn_history(&teacher, cost, &mut model, history);
+        model
+    }
+}
+
+/// Momentum resetting its velocity once the cost stopped improving for `patience` epochs
+///
+/// See `training::MomentumReset`. Each call to `Training::teach_batch` counts as one epoch, which
+/// is how `learn_epochs` teaches.
+pub struct MomentumReset {
+    /// Parameters of the momentum training
+    pub momentum: Momentum,
+    /// Number of epochs without improvement before the velocity is reset
+    pub patience: usize,
+}
+
+impl<M> Teacher<M> for MomentumReset
+    where M: Model
+{
+    type Training = training::MomentumReset<M>;
+
+    fn new_training(&self, model: &M) -> training::MomentumReset<M> {
+        training::MomentumReset {
+            momentum: self.momentum.new_training(model),
+            patience: self.patience,
+            best_cost: f64::INFINITY,
+            epochs_without_improvement: 0,
+            last_epoch_cost: f64::INFINITY,
+            resets: 0,
+        }
+    }
+}
+
 /// Nesterov accelerated gradient descent
 pub struct Nesterov {
     /// Start learning rate
@@ -120,6 +846,7 @@ impl<M> Teacher<M> for Nesterov
             inertia: self.inertia,
             learned_events: 0.0,
             velocity: velocity,
+            last_update: vec![0.0; model.num_coefficents()],
             model_type: PhantomData {},
         }
     }
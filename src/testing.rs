@@ -0,0 +1,40 @@
+use Model;
+use coefficent_values;
+use num_traits::Float;
+
+/// Panics unless `actual` differs from `expected` by at most `tolerance`
+pub fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    if !(Float::abs(actual - expected) <= tolerance) {
+        panic!("assertion failed: {} is not within {} of {}",
+               actual,
+               tolerance,
+               expected);
+    }
+}
+
+/// `true` if `a` and `b` have the same number of coefficents, and each pair of coefficents
+/// differs by at most `tolerance`
+pub fn model_close<M>(a: &M, b: &M, tolerance: f64) -> bool
+    where M: Model
+{
+    a.num_coefficents() == b.num_coefficents() &&
+    coefficent_values(a)
+        .zip(coefficent_values(b))
+        .all(|(a, b)| Float::abs(a - b) <= tolerance)
+}
+
+/// Approximate comparison of models, implemented for every `Model`
+///
+/// Use this in tests rather than `PartialEq`, which compares coefficents exactly.
+pub trait ApproxEq {
+    /// `true` if each pair of coefficents differs by at most `tolerance`, see `model_close`
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool;
+}
+
+impl<M> ApproxEq for M
+    where M: Model
+{
+    fn approx_eq(&self, other: &M, tolerance: f64) -> bool {
+        model_close(self, other, tolerance)
+    }
+}
@@ -1,18 +1,1410 @@
 use Model;
 use Cost;
 use Training;
+use Error;
+use {full_gradient, gradient_norm, mean_cost};
 use std::marker::PhantomData;
+use linear_algebra::KahanSum;
+use rustc_serialize::{json, Encodable, Decodable, Encoder, Decoder};
+#[cfg(feature="rand")]
+use rand::{Rng, XorShiftRng};
+#[cfg(feature="rand")]
+use rand::distributions::normal::StandardNormal;
+
+// Implements `Encodable` and `Decodable` for a training with the listed fields. The
+// `model_type` marker is not serialized, since `PhantomData` is decoded without consuming the
+// `null` it is encoded as, which breaks decoding the checkpoint tuple of training and model.
+macro_rules! serializable_training {
+    ($name:ident { $($field:ident),* }) => {
+        impl<M> Encodable for $name<M>
+            where M: Model
+        {
+            fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+                let len = [$(stringify!($field)),*].len();
+                s.emit_struct(stringify!($name), len, |s| {
+                    encode_fields!(self, s, 0, $($field),*);
+                    Ok(())
+                })
+            }
+        }
+
+        impl<M> Decodable for $name<M>
+            where M: Model
+        {
+            fn decode<D: Decoder>(d: &mut D) -> Result<$name<M>, D::Error> {
+                let len = [$(stringify!($field)),*].len();
+                d.read_struct(stringify!($name), len, |d| {
+                    decode_fields!(d, 0, $($field),*);
+                    Ok($name {
+                        $($field: $field,)*
+                        model_type: PhantomData {},
+                    })
+                })
+            }
+        }
+    }
+}
+
+macro_rules! encode_fields {
+    ($this:ident, $s:ident, $idx:expr, ) => {};
+    ($this:ident, $s:ident, $idx:expr, $field:ident $(, $rest:ident)*) => {
+        $s.emit_struct_field(stringify!($field), $idx, |s| $this.$field.encode(s))?;
+        encode_fields!($this, $s, $idx + 1, $($rest),*);
+    };
+}
+
+macro_rules! decode_fields {
+    ($d:ident, $idx:expr, ) => {};
+    ($d:ident, $idx:expr, $field:ident $(, $rest:ident)*) => {
+        let $field = $d.read_struct_field(stringify!($field), $idx, Decodable::decode)?;
+        decode_fields!($d, $idx + 1, $($rest),*);
+    };
+}
 
 /// Stochastic gradient descent (SGD) with constant learning rate and no momentum
 pub struct GradientDescent<M: Model> {
     /// Defines how fast the coefficients of the trained `Model` will change
     pub learning_rate: f64,
 
-    /// Associated `Model` type
-    pub model_type: PhantomData<M>,
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { GradientDescent { learning_rate } }
+
+impl<M> Training for GradientDescent<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let prediction = model.predict(features);
+
+        for ci in 0..model.num_coefficents() {
+            *model.coefficent(ci) = *model.coefficent(ci) -
+                                    self.learning_rate *
+                                    cost.gradient_full(prediction, truth, model, features, ci);
+        }
+    }
+}
+
+/// Stochastic gradient descent (SGD) with constant learning rate, skipping random coefficent
+/// updates
+///
+/// Each step the update of every coefficent is skipped with a probability of `dropout`. This
+/// reduces co-adaptation of coefficents. Predictions always use all coefficents.
+#[cfg(feature="rand")]
+pub struct GradientDescentDropout<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Probability of skipping the update of a coefficent
+    pub dropout: f64,
+
+    /// Decides which coefficent updates are skipped
+    pub rng: XorShiftRng,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+#[cfg(feature="rand")]
+impl<M> Training for GradientDescentDropout<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        for (ci, g) in gradient.iter().enumerate() {
+            if self.rng.next_f64() < self.dropout {
+                continue;
+            }
+            *model.coefficent(ci) = *model.coefficent(ci) - self.learning_rate * g;
+        }
+    }
+}
+
+/// Stochastic gradient descent (SGD) with annealed gaussian noise added to the gradient
+///
+/// The noise of the `t`-th event has a standard deviation of
+/// `noise_scale / (1 + t)^(decay / 2)`, i.e. its variance decays with `(1 + t)^-decay`. Early
+/// on the noise helps to escape poor local minima, later it vanishes, so the training still
+/// converges.
+#[cfg(feature="rand")]
+pub struct GradientNoise<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Standard deviation of the noise for the first event
+    pub noise_scale: f64,
+
+    /// Exponent of the decay of the noise variance
+    pub decay: f64,
+
+    /// Draws the noise
+    pub rng: XorShiftRng,
+
+    /// Number of learned events
+    pub learned_events: usize,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+#[cfg(feature="rand")]
+impl<M> GradientNoise<M>
+    where M: Model
+{
+    /// Standard deviation of the noise added to the gradient of the next event
+    pub fn noise_std_dev(&self) -> f64 {
+        self.noise_scale / (1.0 + self.learned_events as f64).powf(self.decay / 2.0)
+    }
+}
+
+#[cfg(feature="rand")]
+impl<M> Training for GradientNoise<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+        let std_dev = self.noise_std_dev();
+
+        for (ci, g) in gradient.iter().enumerate() {
+            let StandardNormal(noise) = self.rng.gen();
+            *model.coefficent(ci) = *model.coefficent(ci) -
+                                    self.learning_rate * (g + std_dev * noise);
+        }
+
+        self.learned_events += 1;
+    }
+}
+
+/// Stochastic gradient descent (SGD) sampling events proportionally to their cost
+///
+/// Only `teach_batch` samples events: It draws as many events from the batch as it contains,
+/// each with a probability proportional to an estimate of its cost. Every step is divided by
+/// the sampling probability times the number of events, so the expected update equals the one
+/// of uniform sampling. To keep these corrections bounded, half of the sampling probability is
+/// distributed uniformly. `teach_event` performs a regular SGD step.
+#[cfg(feature="rand")]
+pub struct ImportanceSgd<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Draws the events to learn
+    pub rng: XorShiftRng,
+
+    /// Estimated cost of each event in the current batch, updated whenever an event is drawn
+    ///
+    /// Recalculated for every event at the start of each call to `teach_batch`, since successive
+    /// batches may contain different events.
+    pub costs: Vec<f64>,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+#[cfg(feature="rand")]
+impl<M> ImportanceSgd<M>
+    where M: Model
+{
+    fn step<C, Truth>(&mut self,
+                      cost: &C,
+                      model: &mut M,
+                      features: &M::Input,
+                      truth: Truth,
+                      learning_rate: f64)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        for (ci, g) in gradient.iter().enumerate() {
+            *model.coefficent(ci) = *model.coefficent(ci) - learning_rate * g;
+        }
+    }
+}
+
+#[cfg(feature="rand")]
+impl<M> Training for ImportanceSgd<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let learning_rate = self.learning_rate;
+        self.step(cost, model, features, truth, learning_rate);
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
+        }
+        self.costs = events.iter()
+            .map(|&(ref features, truth)| cost.cost(model.predict(features), truth))
+            .collect();
+
+        let n = events.len() as f64;
+        for _ in 0..events.len() {
+            let total: f64 = self.costs.iter().sum();
+            let probability = |c: f64| {
+                if total > 0.0 {
+                    0.5 / n + 0.5 * c / total
+                } else {
+                    1.0 / n
+                }
+            };
+
+            // Draw an event by inverting the cumulative distribution
+            let mut draw = self.rng.next_f64();
+            let mut index = events.len() - 1;
+            for (i, &c) in self.costs.iter().enumerate() {
+                draw = draw - probability(c);
+                if draw < 0.0 {
+                    index = i;
+                    break;
+                }
+            }
+
+            let learning_rate = self.learning_rate / (n * probability(self.costs[index]));
+            let (ref features, truth) = events[index];
+            self.step(cost, model, features, truth, learning_rate);
+            self.costs[index] = cost.cost(model.predict(features), truth);
+        }
+    }
+}
+
+/// Minimum, maximum and mean magnitude of the gradients seen for one coefficent
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientStatistics {
+    /// Smallest absolute value of the gradient
+    pub min: f64,
+    /// Largest absolute value of the gradient
+    pub max: f64,
+    /// Mean absolute value of the gradient
+    pub mean: f64,
+    /// Number of gradients recorded
+    pub count: usize,
+}
+
+impl Default for GradientStatistics {
+    fn default() -> GradientStatistics {
+        GradientStatistics::new()
+    }
+}
+
+impl GradientStatistics {
+    /// Statistics without any recorded gradients
+    pub fn new() -> GradientStatistics {
+        GradientStatistics {
+            min: ::std::f64::INFINITY,
+            max: 0.0,
+            mean: 0.0,
+            count: 0,
+        }
+    }
+
+    fn record(&mut self, gradient: f64) {
+        let magnitude = gradient.abs();
+        self.min = self.min.min(magnitude);
+        self.max = self.max.max(magnitude);
+        self.count += 1;
+        self.mean = self.mean + (magnitude - self.mean) / self.count as f64;
+    }
+}
+
+/// Records statistics about the gradients of each coefficent, before passing events to `training`
+///
+/// Helps to spot dead coefficents, whose gradient is always zero, or exploding ones.
+pub struct GradientMonitor<T: Training> {
+    /// Training actually changing the coefficents
+    pub training: T,
+
+    /// Statistics for each coefficent
+    pub statistics: Vec<GradientStatistics>,
+}
+
+impl<T> GradientMonitor<T>
+    where T: Training
+{
+    /// Statistics about the gradient of each coefficent
+    pub fn summary(&self) -> &[GradientStatistics] {
+        &self.statistics
+    }
+}
+
+impl<T> Training for GradientMonitor<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        for (statistics, g) in self.statistics.iter_mut().zip(gradient) {
+            statistics.record(g);
+        }
+
+        self.training.teach_event(cost, model, features, truth);
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        for &(ref features, truth) in events {
+            let gradient = full_gradient(cost, model, features, truth);
+            for (statistics, g) in self.statistics.iter_mut().zip(gradient) {
+                statistics.record(g);
+            }
+        }
+
+        self.training.teach_batch(cost, model, events);
+    }
+}
+
+/// Passes the first `remaining_events` events to `first` and every event after to `second`
+///
+/// The switch is counted in events, not in calls to `teach_batch`. A batch containing the switch
+/// is split, its first part is taught by `first` and the rest by `second`.
+pub struct Then<A, B> {
+    /// Training used in the beginning
+    pub first: A,
+    /// Training used once `first` has been taught its events
+    pub second: B,
+    /// Number of events still to be taught by `first`
+    pub remaining_events: usize,
+}
+
+impl<A, B> Training for Then<A, B>
+    where A: Training,
+          B: Training<Model = A::Model>
+{
+    type Model = A::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        if self.remaining_events > 0 {
+            self.first.current_learning_rate()
+        } else {
+            self.second.current_learning_rate()
+        }
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut A::Model,
+                             features: &<A::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if self.remaining_events > 0 {
+            self.remaining_events -= 1;
+            self.first.teach_event(cost, model, features, truth);
+        } else {
+            self.second.teach_event(cost, model, features, truth);
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut A::Model,
+                             events: &[(<A::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let split = self.remaining_events.min(events.len());
+        self.remaining_events -= split;
+        if split > 0 {
+            self.first.teach_batch(cost, model, &events[..split]);
+        }
+        if split < events.len() {
+            self.second.teach_batch(cost, model, &events[split..]);
+        }
+    }
+}
+
+/// Skips events whose residual exceeds `k` robust standard deviations and passes all others
+/// to `training`
+///
+/// The magnitude of the residual is measured as the square root of the cost, which is the
+/// absolute error for `cost::LeastSquares`. The robust standard deviation is estimated as
+/// `1.4826` times the median absolute residual plus `floor`. All of the first `warmup` events
+/// are taught, their median absolute residual initializes the estimate. Afterwards the estimate
+/// follows the median of every event, including skipped ones, by multiplicative steps of
+/// `ADAPTATION`. Within a batch all events are judged before any is taught, the remaining runs
+/// of accepted events are then passed to `training` as batches.
+pub struct RejectOutliers<T: Training> {
+    /// Training of the accepted events
+    pub training: T,
+    /// Events with a residual larger than `k` robust standard deviations are skipped
+    pub k: f64,
+    /// Number of events taught before the filter is active
+    pub warmup: usize,
+    /// Added to the estimated robust standard deviation, about `1e-3` times the typical
+    /// magnitude of the truths is recommended
+    pub floor: f64,
+    /// Absolute residuals seen during the warmup
+    pub warmup_residuals: Vec<f64>,
+    /// Current estimate of the median absolute residual
+    pub median_absolute_residual: f64,
+    /// Number of skipped events
+    pub skipped: usize,
+}
+
+impl<T> RejectOutliers<T>
+    where T: Training
+{
+    /// Relative change of the median absolute residual estimate per event
+    pub const ADAPTATION: f64 = 0.01;
+
+    /// Current estimate of the robust standard deviation of the residuals
+    pub fn robust_std_dev(&self) -> f64 {
+        1.4826 * self.median_absolute_residual + self.floor
+    }
+
+    // Updates the residual statistics with an event and returns whether it should be taught
+    fn accept<C, Truth>(&mut self,
+                        cost: &C,
+                        model: &T::Model,
+                        features: &<T::Model as Model>::Input,
+                        truth: Truth)
+                        -> bool
+        where C: Cost<Truth>
+    {
+        let residual = cost.cost(model.predict(features), truth).sqrt();
+
+        if self.warmup_residuals.len() < self.warmup {
+            self.warmup_residuals.push(residual);
+            if self.warmup_residuals.len() == self.warmup {
+                let mut sorted = self.warmup_residuals.clone();
+                sorted.sort_by(|a, b| a.partial_cmp(b).expect("residual must not be NaN"));
+                self.median_absolute_residual = sorted[sorted.len() / 2];
+            }
+            return true;
+        }
+
+        let outlier = residual > self.k * self.robust_std_dev();
+        if residual > self.median_absolute_residual {
+            self.median_absolute_residual *= 1.0 + Self::ADAPTATION;
+        } else {
+            self.median_absolute_residual *= 1.0 - Self::ADAPTATION;
+        }
+
+        if outlier {
+            self.skipped += 1;
+        }
+        !outlier
+    }
+}
+
+impl<T> Training for RejectOutliers<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if self.accept(cost, model, features, truth) {
+            self.training.teach_event(cost, model, features, truth);
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let accepted: Vec<bool> = events.iter()
+            .map(|&(ref features, truth)| self.accept(cost, model, features, truth))
+            .collect();
+
+        // Teach each run of consecutive accepted events as one batch
+        let mut start = 0;
+        while start < events.len() {
+            if !accepted[start] {
+                start += 1;
+                continue;
+            }
+            let end = accepted[start..]
+                .iter()
+                .position(|&a| !a)
+                .map_or(events.len(), |length| start + length);
+            self.training.teach_batch(cost, model, &events[start..end]);
+            start = end;
+        }
+    }
+}
+
+/// Maintains an exponential moving average of the coefficents changed by `training`
+///
+/// After every event or batch, each coefficent of the average is updated to `decay * average +
+/// (1 - decay) * coefficent`. The averaged model is less noisy than the one trained and often
+/// generalizes better, so it is a good choice for deployment.
+pub struct Ema<T: Training> {
+    /// Training actually changing the coefficents
+    pub training: T,
+    /// Weight of the previous average, between 0 and 1
+    pub decay: f64,
+    /// Model holding the averaged coefficents
+    pub average: T::Model,
+}
+
+impl<T> Ema<T>
+    where T: Training
+{
+    /// Model with the averaged coefficents
+    pub fn ema_model(&self) -> &T::Model {
+        &self.average
+    }
+
+    fn update_average(&mut self, model: &mut T::Model) {
+        for ci in 0..model.num_coefficents() {
+            let average = self.average.coefficent(ci);
+            *average = self.decay * *average + (1.0 - self.decay) * *model.coefficent(ci);
+        }
+    }
+}
+
+impl<T> Training for Ema<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.training.teach_event(cost, model, features, truth);
+        self.update_average(model);
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.training.teach_batch(cost, model, events);
+        self.update_average(model);
+    }
+}
+
+/// Clamps each coefficent into `[lower[ci], upper[ci]]` after every event or batch taught by
+/// `training`
+pub struct Constrained<T: Training> {
+    /// Training actually changing the coefficents
+    pub training: T,
+    /// Lower bound of each coefficent
+    pub lower: Vec<f64>,
+    /// Upper bound of each coefficent
+    pub upper: Vec<f64>,
+}
+
+impl<T> Constrained<T>
+    where T: Training
+{
+    fn clamp(&self, model: &mut T::Model) {
+        for ci in 0..model.num_coefficents() {
+            let coefficent = model.coefficent(ci);
+            *coefficent = coefficent.max(self.lower[ci]).min(self.upper[ci]);
+        }
+    }
+}
+
+impl<T> Training for Constrained<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.training.teach_event(cost, model, features, truth);
+        self.clamp(model);
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.training.teach_batch(cost, model, events);
+        self.clamp(model);
+    }
+}
+
+/// Keeps the coefficents listed in `frozen` at their values while `training` changes the others
+///
+/// Frozen coefficents are reset after every event or batch, so this works with any `training`.
+/// Stateful trainings, e.g. `Momentum`, still track a velocity for them, which is simply
+/// discarded.
+pub struct FreezeCoefficents<T: Training> {
+    /// Training changing the coefficents which are not frozen
+    pub training: T,
+    /// Indices of the coefficents which must not change
+    pub frozen: Vec<usize>,
+}
+
+impl<T> Training for FreezeCoefficents<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let values: Vec<f64> = self.frozen.iter().map(|&ci| *model.coefficent(ci)).collect();
+
+        self.training.teach_event(cost, model, features, truth);
+
+        for (&ci, value) in self.frozen.iter().zip(values) {
+            *model.coefficent(ci) = value;
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let values: Vec<f64> = self.frozen.iter().map(|&ci| *model.coefficent(ci)).collect();
+
+        self.training.teach_batch(cost, model, events);
+
+        for (&ci, value) in self.frozen.iter().zip(values) {
+            *model.coefficent(ci) = value;
+        }
+    }
+}
+
+/// Rescales the gradient of each event to an L2 norm of at most `max_norm`, before passing the
+/// event to `training`
+///
+/// All coefficents are scaled by the same factor, so the direction of the gradient is kept. This
+/// is done by scaling the outer derivative of the cost, hence it works with any `training`. A
+/// batch is scaled as a whole, so the mean gradient of its events has a norm of at most
+/// `max_norm`.
+pub struct ClipGradientNorm<T: Training> {
+    /// Training actually changing the coefficents
+    pub training: T,
+    /// Largest norm of the gradient of a single event
+    pub max_norm: f64,
+    /// Number of events whose gradient has been rescaled
+    pub clipped: usize,
+}
+
+// Cost with its outer derivative, and therefore its gradient, multiplied by `factor`
+struct ScaledGradient<'a, C: 'a> {
+    cost: &'a C,
+    factor: f64,
+}
+
+impl<'a, C, Truth> Cost<Truth> for ScaledGradient<'a, C>
+    where C: Cost<Truth>
+{
+    fn outer_derivative(&self, prediction: f64, truth: Truth) -> f64 {
+        self.factor * self.cost.outer_derivative(prediction, truth)
+    }
+
+    fn gradient_full<M>(&self,
+                        prediction: f64,
+                        truth: Truth,
+                        model: &M,
+                        features: &M::Input,
+                        coefficent: usize)
+                        -> f64
+        where M: Model
+    {
+        self.factor * self.cost.gradient_full(prediction, truth, model, features, coefficent)
+    }
+
+    fn cost(&self, prediction: f64, truth: Truth) -> f64 {
+        self.cost.cost(prediction, truth)
+    }
+}
+
+impl<T> Training for ClipGradientNorm<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let norm = gradient_norm(cost, model, features, truth);
+        if norm > self.max_norm {
+            self.clipped += 1;
+            let scaled = ScaledGradient {
+                cost: cost,
+                factor: self.max_norm / norm,
+            };
+            self.training.teach_event(&scaled, model, features, truth);
+        } else {
+            self.training.teach_event(cost, model, features, truth);
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
+        }
+        let sum = summed_gradient(cost, model, events);
+        let norm = dot(&sum, &sum).sqrt() / events.len() as f64;
+        if norm > self.max_norm {
+            self.clipped += events.len();
+            let scaled = ScaledGradient {
+                cost: cost,
+                factor: self.max_norm / norm,
+            };
+            self.training.teach_batch(&scaled, model, events);
+        } else {
+            self.training.teach_batch(cost, model, events);
+        }
+    }
+}
+
+/// Scales the gradient of every event taught by `training` by the inverse of the local noise
+///
+/// The noise is estimated as the robust standard deviation, `1.4826` times the median absolute
+/// residual, of the last `window` events, including the current one. Residuals are measured as
+/// the square root of the cost, like `RejectOutliers` does. Regions of the stream with a lot of
+/// noise therefore contribute smaller steps than regions with little noise. A batch records the
+/// residuals of all its events before it is taught with a single scale. The estimate is
+/// bounded below by `noise_floor`, so well fitted regions do not produce arbitrarily large
+/// steps.
+pub struct RobustScaled<T: Training> {
+    /// Training changing the coefficents with the scaled gradient
+    pub training: T,
+    /// Number of recent events the noise is estimated from
+    pub window: usize,
+    /// Lower bound of the noise estimate
+    pub noise_floor: f64,
+    /// Absolute residuals of the last `window` events, used as a ring buffer
+    pub residuals: Vec<f64>,
+    /// Position in `residuals` the next residual is written to
+    pub position: usize,
+}
+
+impl<T> RobustScaled<T>
+    where T: Training
+{
+    /// Noise estimated from the residuals in the current window
+    pub fn noise(&self) -> f64 {
+        if self.residuals.is_empty() {
+            return self.noise_floor;
+        }
+        let mut sorted = self.residuals.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).expect("residual must not be NaN"));
+        let robust_std_dev = 1.4826 * sorted[sorted.len() / 2];
+        if robust_std_dev > self.noise_floor {
+            robust_std_dev
+        } else {
+            self.noise_floor
+        }
+    }
+
+    fn record<C, Truth>(&mut self,
+                        cost: &C,
+                        model: &T::Model,
+                        features: &<T::Model as Model>::Input,
+                        truth: Truth)
+        where C: Cost<Truth>
+    {
+        let residual = cost.cost(model.predict(features), truth).sqrt();
+        if self.residuals.len() < self.window {
+            self.residuals.push(residual);
+        } else {
+            self.residuals[self.position] = residual;
+        }
+        self.position = (self.position + 1) % self.window;
+    }
+}
+
+impl<T> Training for RobustScaled<T>
+    where T: Training
+{
+    type Model = T::Model;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.training.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             features: &<T::Model as Model>::Input,
+                             truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.record(cost, model, features, truth);
+
+        let scaled = ScaledGradient {
+            cost: cost,
+            factor: 1.0 / self.noise(),
+        };
+        self.training.teach_event(&scaled, model, features, truth);
+    }
+
+    fn teach_batch<C, Truth>(&mut self,
+                             cost: &C,
+                             model: &mut T::Model,
+                             events: &[(<T::Model as Model>::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        for &(ref features, truth) in events {
+            self.record(cost, model, features, truth);
+        }
+
+        let scaled = ScaledGradient {
+            cost: cost,
+            factor: 1.0 / self.noise(),
+        };
+        self.training.teach_batch(&scaled, model, events);
+    }
+}
+
+/// Gradient descent scaled by an estimate of the diagonal of the Hessian
+///
+/// The diagonal is estimated using Hutchinson's method: The gradient is evaluated a second
+/// time with all coefficents shifted by a random vector `z` of `+-1`. The change in the
+/// gradient approximates the Hessian times `z`, multiplication with `z` yields an estimate of the
+/// diagonal. Each coefficent is updated by `learning_rate * gradient / (sqrt(h) + epsilon)`,
+/// with `h` being a moving average of the squared estimates.
+///
+/// Probing requires an additional prediction and evaluation of the gradient, so only every
+/// `probe_interval`-th event is probed. Larger intervals make trainings cheaper, but slower to
+/// adapt to changes in curvature.
+#[cfg(feature="rand")]
+pub struct AdaHessian<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Weight of the previous average of the Hessian estimates, between 0 and 1
+    pub decay: f64,
+
+    /// Added to the denominator to avoid divisions by zero
+    pub epsilon: f64,
+
+    /// Number of events between two estimations of the Hessian
+    pub probe_interval: usize,
+
+    /// Draws the random probe vectors
+    pub rng: XorShiftRng,
+
+    /// Moving average of the squared estimates of the diagonal of the Hessian
+    pub hessian: Vec<f64>,
+
+    /// Change of each coefficent during the last event
+    pub last_update: Vec<f64>,
+
+    /// Number of estimations taken so far, used to correct the bias of the moving average
+    pub probes: i32,
+
+    /// Number of learned events
+    pub learned_events: usize,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+#[cfg(feature="rand")]
+impl<M> AdaHessian<M>
+    where M: Model
+{
+    // Distance the coefficents are shifted to approximate the Hessian
+    const PROBE_STEP: f64 = 1e-4;
+
+    /// Change of each coefficent during the last event
+    pub fn last_update(&self) -> &[f64] {
+        &self.last_update
+    }
+
+    fn probe<C, Truth>(&mut self,
+                       cost: &C,
+                       model: &M,
+                       features: &M::Input,
+                       truth: Truth,
+                       gradient: &[f64])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let z: Vec<f64> = (0..gradient.len())
+            .map(|_| if self.rng.gen() { 1.0 } else { -1.0 })
+            .collect();
+        let mut shifted = model.clone();
+        for (ci, z_i) in z.iter().enumerate() {
+            *shifted.coefficent(ci) = *shifted.coefficent(ci) + Self::PROBE_STEP * z_i;
+        }
+        let shifted_gradient = full_gradient(cost, &shifted, features, truth);
+        self.probes += 1;
+        for ci in 0..gradient.len() {
+            let diagonal = z[ci] * (shifted_gradient[ci] - gradient[ci]) / Self::PROBE_STEP;
+            self.hessian[ci] = self.decay * self.hessian[ci] +
+                               (1.0 - self.decay) * diagonal * diagonal;
+        }
+    }
+}
+
+#[cfg(feature="rand")]
+impl<M> Training for AdaHessian<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        if self.learned_events % self.probe_interval == 0 {
+            self.probe(cost, model, features, truth, &gradient);
+        }
+
+        let bias_correction = 1.0 - self.decay.powi(self.probes);
+        for (ci, g) in gradient.iter().enumerate() {
+            let curvature = (self.hessian[ci] / bias_correction).sqrt();
+            self.last_update[ci] = -self.learning_rate * g / (curvature + self.epsilon);
+            *model.coefficent(ci) = *model.coefficent(ci) + self.last_update[ci];
+        }
+
+        self.learned_events += 1;
+    }
+}
+
+/// Stochastic gradient descent (SGD) minimizing the cost plus `lambda` times the squared
+/// coefficents (ridge regularization)
+pub struct Ridge<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Strength of the penalty
+    pub lambda: f64,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { Ridge { learning_rate, lambda } }
+
+impl<M> Training for Ridge<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        for (ci, g) in gradient.iter().enumerate() {
+            let penalty = 2.0 * self.lambda * *model.coefficent(ci);
+            *model.coefficent(ci) = *model.coefficent(ci) - self.learning_rate * (g + penalty);
+        }
+    }
+}
+
+/// Stochastic gradient descent (SGD) with truncated gradient L1 regularization
+///
+/// Every `period` events coefficents with an absolute value no larger than `theta` are shrunk
+/// towards zero by `period * learning_rate * gravity`. Coefficents crossing zero are truncated
+/// to exactly zero, which yields sparse models during online learning.
+pub struct TruncatedGradient<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+    /// Strength of the L1 penalty
+    pub gravity: f64,
+    /// Only coefficents with an absolute value of at most `theta` are truncated
+    pub theta: f64,
+    /// Number of events between two truncations
+    pub period: usize,
+    /// Number of learned events
+    pub learned_events: usize,
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! {
+    TruncatedGradient {
+        learning_rate, gravity, theta, period, learned_events
+    }
+}
+
+impl<M> Training for TruncatedGradient<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+
+        for (ci, g) in gradient.iter().enumerate() {
+            *model.coefficent(ci) = *model.coefficent(ci) - self.learning_rate * g;
+        }
+
+        self.learned_events += 1;
+        if self.learned_events % self.period == 0 {
+            let alpha = self.period as f64 * self.learning_rate * self.gravity;
+            for ci in 0..model.num_coefficents() {
+                let coefficent = model.coefficent(ci);
+                // A checkpoint restores an infinite `theta` as `NaN`, which must still truncate
+                // every coefficent
+                if coefficent.abs() > self.theta {
+                    continue;
+                }
+                if *coefficent >= 0.0 {
+                    *coefficent = (*coefficent - alpha).max(0.0);
+                } else {
+                    *coefficent = (*coefficent + alpha).min(0.0);
+                }
+            }
+        }
+    }
+}
+
+/// Limited-memory BFGS (L-BFGS), a quasi-Newton method for batch optimization
+///
+/// Each call to `teach_batch` performs one step minimizing the mean cost of the whole batch, so
+/// pass the full history every time, e.g. using `learn_epochs`. The search direction is derived
+/// from the changes of the coefficents and gradients during the last `memory` steps using the
+/// two-loop recursion. The step length is found by a backtracking line search satisfying the
+/// Armijo condition. `teach_event` performs the same step for the cost of a single event, which
+/// is rarely useful as the curvature estimate mixes different events.
+pub struct Lbfgs<M: Model> {
+    /// Number of recent steps used to approximate the inverse Hessian
+    pub memory: usize,
+
+    /// Change of the coefficents during the most recent steps, oldest first
+    pub coefficent_changes: Vec<Vec<f64>>,
+
+    /// Change of the gradients during the most recent steps, oldest first
+    pub gradient_changes: Vec<Vec<f64>>,
+
+    /// Coefficents and gradient at the start of the last step
+    pub previous: Option<(Vec<f64>, Vec<f64>)>,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { Lbfgs { memory, coefficent_changes, gradient_changes, previous } }
+
+impl<M> Lbfgs<M>
+    where M: Model
+{
+    /// Maximum number of halvings of the step length during the line search
+    const MAX_BACKTRACKING: usize = 40;
+
+    fn step<O, G>(&mut self, model: &mut M, objective: O, gradient: G)
+        where O: Fn(&M) -> f64,
+              G: Fn(&M) -> Vec<f64>
+    {
+        let n = model.num_coefficents();
+        let x: Vec<f64> = (0..n).map(|ci| *model.coefficent(ci)).collect();
+        let g = gradient(model);
+
+        if let Some((ref x_prev, ref g_prev)) = self.previous {
+            let s: Vec<f64> = x.iter().zip(x_prev).map(|(a, b)| a - b).collect();
+            let y: Vec<f64> = g.iter().zip(g_prev).map(|(a, b)| a - b).collect();
+            // Skip updates violating the curvature condition, they would break the positive
+            // definiteness of the inverse Hessian estimate
+            if self.memory > 0 && dot(&s, &y) > 1e-10 {
+                if self.coefficent_changes.len() == self.memory {
+                    self.coefficent_changes.remove(0);
+                    self.gradient_changes.remove(0);
+                }
+                self.coefficent_changes.push(s);
+                self.gradient_changes.push(y);
+            }
+        }
+
+        // Two-loop recursion computing the direction `-H * g`
+        let mut q = g.clone();
+        let mut alphas = Vec::with_capacity(self.coefficent_changes.len());
+        for (s, y) in self.coefficent_changes.iter().zip(&self.gradient_changes).rev() {
+            let alpha = dot(s, &q) / dot(y, s);
+            for (q, y) in q.iter_mut().zip(y) {
+                *q -= alpha * y;
+            }
+            alphas.push(alpha);
+        }
+        if let (Some(s), Some(y)) = (self.coefficent_changes.last(), self.gradient_changes.last()) {
+            let gamma = dot(s, y) / dot(y, y);
+            for q in &mut q {
+                *q *= gamma;
+            }
+        }
+        for ((s, y), alpha) in self.coefficent_changes
+            .iter()
+            .zip(&self.gradient_changes)
+            .zip(alphas.into_iter().rev()) {
+            let beta = dot(y, &q) / dot(y, s);
+            for (q, s) in q.iter_mut().zip(s) {
+                *q += (alpha - beta) * s;
+            }
+        }
+        let direction: Vec<f64> = q.iter().map(|q| -q).collect();
+
+        // Backtracking line search
+        let f = objective(model);
+        let slope = dot(&g, &direction);
+        // Without curvature information the direction is the raw gradient, so start with a
+        // step of at most unit length
+        let mut step_length = if self.coefficent_changes.is_empty() {
+            1.0 / dot(&g, &g).sqrt().max(1.0)
+        } else {
+            1.0
+        };
+        let mut decreased = false;
+        for _ in 0..Self::MAX_BACKTRACKING {
+            for ci in 0..n {
+                *model.coefficent(ci) = x[ci] + step_length * direction[ci];
+            }
+            if objective(model) <= f + 1e-4 * step_length * slope {
+                decreased = true;
+                break;
+            }
+            step_length *= 0.5;
+        }
+        // No step length improved the objective, keep the coefficents
+        if !decreased {
+            for ci in 0..n {
+                *model.coefficent(ci) = x[ci];
+            }
+        }
+
+        self.previous = Some((x, g));
+    }
+}
+
+// Sum of the gradients of all `events`, accumulated with compensated summation so precision does
+// not degrade for large batches
+fn summed_gradient<M, C, Truth>(cost: &C, model: &M, events: &[(M::Input, Truth)]) -> Vec<f64>
+    where M: Model,
+          C: Cost<Truth>,
+          Truth: Copy
+{
+    let mut sum = vec![KahanSum::new(); model.num_coefficents()];
+    for &(ref features, truth) in events {
+        for (s, g) in sum.iter_mut().zip(full_gradient(cost, model, features, truth)) {
+            s.add(g);
+        }
+    }
+    sum.into_iter().map(|s| s.sum()).collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+impl<M> Training for Lbfgs<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.step(model,
+                  |model| cost.cost(model.predict(features), truth),
+                  |model| full_gradient(cost, model, features, truth));
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
+        }
+        let n = events.len() as f64;
+        self.step(model,
+                  |model| mean_cost(cost, model, events),
+                  |model| {
+            summed_gradient(cost, model, events).into_iter().map(|g| g / n).collect()
+        });
+    }
+}
+
+/// Resilient backpropagation (iRprop-), adapting a step size per coefficent from gradient signs
+///
+/// Only the sign of the gradient is used. Each coefficent moves by its own step size against
+/// the sign of its gradient. The step size grows by `INCREASE` while the sign stays the same and
+/// shrinks by `DECREASE` once it flips, in which case the coefficent is not changed for this
+/// step. Rprop requires no learning rate, yet it relies on consistent gradients, so pass the full
+/// history to `teach_batch` every time. A step in which every gradient flips its sign leaves the
+/// model unchanged, so prefer a fixed number of steps over stopping once the cost stalls.
+pub struct Rprop<M: Model> {
+    /// Current step size of each coefficent
+    pub step_sizes: Vec<f64>,
+
+    /// Gradient of the previous step, zero for coefficents which changed sign
+    pub previous_gradient: Vec<f64>,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { Rprop { step_sizes, previous_gradient } }
+
+impl<M> Rprop<M>
+    where M: Model
+{
+    /// Step size of each coefficent before the first step
+    pub const INITIAL_STEP: f64 = 0.1;
+    /// Factor by which a step size grows while the sign of the gradient is stable
+    pub const INCREASE: f64 = 1.2;
+    /// Factor by which a step size shrinks after the sign of the gradient flipped
+    pub const DECREASE: f64 = 0.5;
+    /// Upper bound of the step sizes
+    pub const MAX_STEP: f64 = 50.0;
+    /// Lower bound of the step sizes
+    pub const MIN_STEP: f64 = 1e-9;
+
+    fn step(&mut self, model: &mut M, gradient: Vec<f64>) {
+        if self.step_sizes.len() != gradient.len() {
+            self.step_sizes = vec![Self::INITIAL_STEP; gradient.len()];
+            self.previous_gradient = vec![0.0; gradient.len()];
+        }
+
+        for (ci, mut g) in gradient.into_iter().enumerate() {
+            let agreement = g * self.previous_gradient[ci];
+            if agreement > 0.0 {
+                self.step_sizes[ci] = (self.step_sizes[ci] * Self::INCREASE).min(Self::MAX_STEP);
+            } else if agreement < 0.0 {
+                self.step_sizes[ci] = (self.step_sizes[ci] * Self::DECREASE).max(Self::MIN_STEP);
+                g = 0.0;
+            }
+            if g > 0.0 {
+                *model.coefficent(ci) -= self.step_sizes[ci];
+            } else if g < 0.0 {
+                *model.coefficent(ci) += self.step_sizes[ci];
+            }
+            self.previous_gradient[ci] = g;
+        }
+    }
 }
 
-impl<M> Training for GradientDescent<M>
+impl<M> Training for Rprop<M>
     where M: Model
 {
     type Model = M;
@@ -21,13 +1413,20 @@ impl<M> Training for GradientDescent<M>
         where C: Cost<Truth>,
               Truth: Copy
     {
-        let prediction = model.predict(features);
+        let gradient = full_gradient(cost, model, features, truth);
+        self.step(model, gradient);
+    }
 
-        for ci in 0..model.num_coefficents() {
-            *model.coefficent(ci) = *model.coefficent(ci) -
-                                    self.learning_rate *
-                                    cost.gradient(prediction, truth, model.gradient(ci, features));
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
         }
+        let n = events.len() as f64;
+        let gradient = summed_gradient(cost, model, events).into_iter().map(|g| g / n).collect();
+        self.step(model, gradient);
     }
 }
 
@@ -49,6 +1448,8 @@ pub struct GradientDescentAl<M: Model> {
     pub model_type: PhantomData<M>,
 }
 
+serializable_training! { GradientDescentAl { l0, t, learned_events } }
+
 impl<M: Model> GradientDescentAl<M> {
     /// Returns current learning rate
     ///
@@ -65,6 +1466,10 @@ impl<M> Training for GradientDescentAl<M>
 {
     type Model = M;
 
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate())
+    }
+
     fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
         where C: Cost<Truth>,
               Truth: Copy
@@ -74,7 +1479,343 @@ impl<M> Training for GradientDescentAl<M>
         for ci in 0..model.num_coefficents() {
             *model.coefficent(ci) = *model.coefficent(ci) -
                                     self.learning_rate() *
-                                    cost.gradient(prediction, truth, model.gradient(ci, features));
+                                    cost.gradient_full(prediction, truth, model, features, ci);
+        }
+
+        self.learned_events = self.learned_events + 1.0;
+    }
+}
+
+/// How the gradients of the events in a batch are combined into a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub enum Reduction {
+    /// Sum of the gradients, so the step grows with the size of the batch
+    Sum,
+    /// Mean of the gradients, so the learning rate means the same for any batch size
+    Mean,
+}
+
+impl Default for Reduction {
+    fn default() -> Reduction {
+        Reduction::Mean
+    }
+}
+
+/// Gradient descent performing a single step per batch
+///
+/// `teach_batch` combines the gradients of all events according to `reduction` and updates the
+/// coefficents once. `teach_event` performs a step for a single event, for which `Sum` and `Mean`
+/// are the same.
+pub struct BatchGradientDescent<M: Model> {
+    /// Defines how fast the coefficients of the trained `Model` will change
+    pub learning_rate: f64,
+
+    /// Whether the gradients of a batch are summed or averaged
+    pub reduction: Reduction,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { BatchGradientDescent { learning_rate, reduction } }
+
+impl<M> Training for BatchGradientDescent<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+        for (ci, g) in gradient.into_iter().enumerate() {
+            *model.coefficent(ci) -= self.learning_rate * g;
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
+        }
+        let sum = summed_gradient(cost, model, events);
+        let scale = match self.reduction {
+            Reduction::Sum => 1.0,
+            Reduction::Mean => 1.0 / events.len() as f64,
+        };
+        for (ci, s) in sum.into_iter().enumerate() {
+            *model.coefficent(ci) -= self.learning_rate * scale * s;
+        }
+    }
+}
+
+/// Batch gradient descent adapting its learning rate to the progress of each epoch ("bold
+/// driver")
+///
+/// Every call to `teach_batch` is an epoch, so pass the full history each time, e.g. using
+/// `learn_epochs`. Before each step the mean cost of the batch is compared to the one before the
+/// previous step. If it decreased, the learning rate is multiplied with `increase`, otherwise
+/// with `decrease`. The learning rate is kept within `[min_learning_rate, max_learning_rate]`.
+/// `teach_event` performs a plain gradient descent step without adapting the learning rate.
+pub struct BoldDriver<M: Model> {
+    /// Current learning rate
+    pub learning_rate: f64,
+
+    /// Factor applied to the learning rate after an epoch which decreased the cost, e.g. `1.05`
+    pub increase: f64,
+
+    /// Factor applied to the learning rate after an epoch which increased the cost, e.g. `0.5`
+    pub decrease: f64,
+
+    /// Lower bound of the learning rate
+    pub min_learning_rate: f64,
+
+    /// Upper bound of the learning rate
+    pub max_learning_rate: f64,
+
+    /// Mean cost of the batch before the last step
+    pub previous_cost: Option<f64>,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! {
+    BoldDriver {
+        learning_rate, increase, decrease, min_learning_rate, max_learning_rate, previous_cost
+    }
+}
+
+impl<M> Training for BoldDriver<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+        for (ci, g) in gradient.into_iter().enumerate() {
+            *model.coefficent(ci) -= self.learning_rate * g;
+        }
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        if events.is_empty() {
+            return;
+        }
+        let current_cost = mean_cost(cost, model, events);
+        if let Some(previous_cost) = self.previous_cost {
+            let factor = if current_cost < previous_cost {
+                self.increase
+            } else {
+                self.decrease
+            };
+            self.learning_rate = (self.learning_rate * factor)
+                .max(self.min_learning_rate)
+                .min(self.max_learning_rate);
+        }
+        self.previous_cost = Some(current_cost);
+
+        let n = events.len() as f64;
+        let sum = summed_gradient(cost, model, events);
+        for (ci, s) in sum.into_iter().enumerate() {
+            *model.coefficent(ci) -= self.learning_rate * s / n;
+        }
+    }
+}
+
+/// Normalized gradient descent, scaling the gradient to unit length
+///
+/// Every step moves the coefficents by exactly `learning_rate` in the direction of the negative
+/// gradient. The step size is therefore independent of the magnitude of the gradient, which
+/// makes training robust to poorly scaled features. `teach_batch` performs a single step along
+/// the summed gradient of all events, `teach_event` a step for a single event. Steps with a zero
+/// gradient are skipped.
+pub struct NormalizedGradientDescent<M: Model> {
+    /// Length of each step in coefficent space
+    pub learning_rate: f64,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { NormalizedGradientDescent { learning_rate } }
+
+impl<M> NormalizedGradientDescent<M>
+    where M: Model
+{
+    fn step(&self, model: &mut M, gradient: Vec<f64>) {
+        let norm = dot(&gradient, &gradient).sqrt();
+        if norm == 0.0 {
+            return;
+        }
+        for (ci, g) in gradient.into_iter().enumerate() {
+            *model.coefficent(ci) -= self.learning_rate * g / norm;
+        }
+    }
+}
+
+impl<M> Training for NormalizedGradientDescent<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate)
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let gradient = full_gradient(cost, model, features, truth);
+        self.step(model, gradient);
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, events: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let sum = summed_gradient(cost, model, events);
+        self.step(model, sum);
+    }
+}
+
+/// Trains a model with a learning rate cycling between `base` and `max`
+///
+/// The learning rate follows a triangular wave. It starts at `base`, reaches `max` after
+/// `step_size` events and is back at `base` after `2 * step_size` events.
+pub struct CyclicalLr<M: Model> {
+    /// Lowest learning rate
+    pub base: f64,
+
+    /// Highest learning rate
+    pub max: f64,
+
+    /// Number of events for a ramp from `base` to `max`
+    pub step_size: f64,
+
+    /// Number of learned events
+    pub learned_events: f64,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { CyclicalLr { base, max, step_size, learned_events } }
+
+impl<M: Model> CyclicalLr<M> {
+    /// Returns current learning rate
+    pub fn learning_rate(&self) -> f64 {
+        let cycle = (self.learned_events / (2.0 * self.step_size)).floor();
+        let x = (self.learned_events / self.step_size - 2.0 * cycle - 1.0).abs();
+        self.base + (self.max - self.base) * (1.0 - x)
+    }
+}
+
+impl<M> Training for CyclicalLr<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate())
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let prediction = model.predict(features);
+        let learning_rate = self.learning_rate();
+
+        for ci in 0..model.num_coefficents() {
+            *model.coefficent(ci) = *model.coefficent(ci) -
+                                    learning_rate *
+                                    cost.gradient_full(prediction, truth, model, features, ci);
+        }
+
+        self.learned_events = self.learned_events + 1.0;
+    }
+}
+
+/// Trains a model with stochastic gradient descent with warm restarts (SGDR)
+///
+/// Within each period the learning rate is annealed from `lr_max` to `lr_min` along half a
+/// cosine wave. Then it restarts at `lr_max`. The first period lasts `t0` events, every following
+/// period is `t_mult` times as long as the previous one.
+pub struct Sgdr<M: Model> {
+    /// Number of events in the first period
+    pub t0: f64,
+
+    /// Factor by which each period is longer than the previous one, at least `1`
+    pub t_mult: f64,
+
+    /// Learning rate at the start of each period
+    pub lr_max: f64,
+
+    /// Learning rate approached at the end of each period
+    pub lr_min: f64,
+
+    /// Number of learned events
+    pub learned_events: f64,
+
+    /// Associated `Model` type
+    pub model_type: PhantomData<M>,
+}
+
+serializable_training! { Sgdr { t0, t_mult, lr_max, lr_min, learned_events } }
+
+impl<M: Model> Sgdr<M> {
+    /// Returns current learning rate
+    pub fn learning_rate(&self) -> f64 {
+        let mut t = self.learned_events;
+        let mut period = self.t0;
+        while t >= period {
+            t -= period;
+            period *= self.t_mult;
+        }
+        self.lr_min +
+        0.5 * (self.lr_max - self.lr_min) * (1.0 + (::std::f64::consts::PI * t / period).cos())
+    }
+}
+
+impl<M> Training for Sgdr<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate())
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let prediction = model.predict(features);
+        let learning_rate = self.learning_rate();
+
+        for ci in 0..model.num_coefficents() {
+            *model.coefficent(ci) = *model.coefficent(ci) -
+                                    learning_rate *
+                                    cost.gradient_full(prediction, truth, model, features, ci);
         }
 
         self.learned_events = self.learned_events + 1.0;
@@ -107,6 +1848,8 @@ pub struct Momentum<M: Model> {
     pub model_type: PhantomData<M>,
 }
 
+serializable_training! { Momentum { l0, t, inertia, learned_events, velocity } }
+
 impl<M: Model> Momentum<M> {
     /// Returns current learning rate
     ///
@@ -116,6 +1859,11 @@ impl<M: Model> Momentum<M> {
     pub fn learning_rate(&self) -> f64 {
         self.l0 / (1.0 + self.learned_events / self.t)
     }
+
+    /// Change of each coefficent during the last event, which equals the velocity
+    pub fn last_update(&self) -> &[f64] {
+        &self.velocity
+    }
 }
 
 impl<M> Training for Momentum<M>
@@ -123,6 +1871,10 @@ impl<M> Training for Momentum<M>
 {
     type Model = M;
 
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate())
+    }
+
     fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
         where C: Cost<Truth>,
               Truth: Copy
@@ -132,7 +1884,7 @@ impl<M> Training for Momentum<M>
         for ci in 0..model.num_coefficents() {
             self.velocity[ci] = self.inertia * self.velocity[ci] -
                                 self.learning_rate() *
-                                cost.gradient(prediction, truth, model.gradient(ci, features));
+                                cost.gradient_full(prediction, truth, model, features, ci);
             *model.coefficent(ci) = *model.coefficent(ci) + self.velocity[ci];
         }
 
@@ -140,6 +1892,77 @@ impl<M> Training for Momentum<M>
     }
 }
 
+/// Momentum resetting its velocity to zero once the cost stops improving
+///
+/// Every call of `teach_batch` is treated as an epoch. Its cost is the mean cost of the events
+/// before they are learned. If it did not improve on the lowest epoch cost so far for `patience`
+/// consecutive epochs, the velocity is reset, giving the optimizer a fresh direction.
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct MomentumReset<M: Model> {
+    /// Momentum training whose velocity gets reset
+    pub momentum: Momentum<M>,
+
+    /// Number of epochs without improvement before the velocity is reset
+    pub patience: usize,
+
+    /// Lowest cost of an epoch so far
+    pub best_cost: f64,
+
+    /// Number of consecutive epochs which did not improve on `best_cost`
+    pub epochs_without_improvement: usize,
+
+    /// Cost of the last epoch
+    pub last_epoch_cost: f64,
+
+    /// Number of resets so far
+    pub resets: usize,
+}
+
+impl<M> Training for MomentumReset<M>
+    where M: Model
+{
+    type Model = M;
+
+    fn current_learning_rate(&self) -> Option<f64> {
+        self.momentum.current_learning_rate()
+    }
+
+    fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        self.momentum.teach_event(cost, model, features, truth);
+    }
+
+    fn teach_batch<C, Truth>(&mut self, cost: &C, model: &mut M, batch: &[(M::Input, Truth)])
+        where C: Cost<Truth>,
+              Truth: Copy
+    {
+        let mut sum = 0.0;
+        for &(ref features, truth) in batch {
+            sum += cost.cost(model.predict(features), truth);
+            self.momentum.teach_event(cost, model, features, truth);
+        }
+        self.last_epoch_cost = sum / batch.len() as f64;
+
+        // A checkpoint restores the infinite `best_cost` of a fresh training as `NaN`
+        if self.last_epoch_cost < self.best_cost || self.best_cost.is_nan() {
+            self.best_cost = self.last_epoch_cost;
+            self.epochs_without_improvement = 0;
+        } else {
+            self.epochs_without_improvement += 1;
+        }
+
+        if self.epochs_without_improvement >= self.patience {
+            for v in &mut self.momentum.velocity {
+                *v = 0.0;
+            }
+            self.epochs_without_improvement = 0;
+            self.resets += 1;
+        }
+    }
+}
+
 /// Nesterov accelerated gradient descent
 ///
 /// Like accelerated gradient descent, Nesterov accelerated gradient descent
@@ -170,10 +1993,15 @@ pub struct Nesterov<M: Model> {
     /// Current velocity of coefficents (in delta per iteration);
     pub velocity: Vec<f64>,
 
+    /// Change of each coefficent during the last event
+    pub last_update: Vec<f64>,
+
     /// Associated `Model` type
     pub model_type: PhantomData<M>,
 }
 
+serializable_training! { Nesterov { l0, t, inertia, learned_events, velocity, last_update } }
+
 impl<M: Model> Nesterov<M> {
     /// Returns current learning rate
     ///
@@ -183,6 +2011,14 @@ impl<M: Model> Nesterov<M> {
     pub fn learning_rate(&self) -> f64 {
         self.l0 / (1.0 + self.learned_events / self.t)
     }
+
+    /// Change of each coefficent during the last event
+    ///
+    /// The sum of the velocity before the event and the gradient step at the estimated new
+    /// position.
+    pub fn last_update(&self) -> &[f64] {
+        &self.last_update
+    }
 }
 
 impl<M> Training for Nesterov<M>
@@ -190,6 +2026,10 @@ impl<M> Training for Nesterov<M>
 {
     type Model = M;
 
+    fn current_learning_rate(&self) -> Option<f64> {
+        Some(self.learning_rate())
+    }
+
     fn teach_event<C, Truth>(&mut self, cost: &C, model: &mut M, features: &M::Input, truth: Truth)
         where C: Cost<Truth>,
               Truth: Copy
@@ -202,11 +2042,42 @@ impl<M> Training for Nesterov<M>
 
         for ci in 0..model.num_coefficents() {
             let delta = -self.learning_rate() *
-                        cost.gradient(prediction, truth, model.gradient(ci, features));
+                        cost.gradient_full(prediction, truth, model, features, ci);
             *model.coefficent(ci) = *model.coefficent(ci) + delta;
+            self.last_update[ci] = self.velocity[ci] + delta;
             self.velocity[ci] = self.inertia * self.velocity[ci] + delta;
         }
 
         self.learned_events = self.learned_events + 1.0;
     }
 }
+
+/// Trainings whose state can be saved together with the model, to resume the training later
+///
+/// In contrast to serializing only the model, this includes the state of the training, e.g. the
+/// velocity of `Momentum` or the number of learned events. Implemented for every serializable
+/// `Training`. Checkpoints are JSON, so coefficents may differ from the saved ones in the last
+/// digit after loading. JSON can not represent infinite numbers, they are restored as `NaN`,
+/// which the trainings treat like the infinite value. Trainings owning a random number
+/// generator (`GradientDescentDropout`, `GradientNoise`, `ImportanceSgd` and `AdaHessian`) and
+/// the trainings wrapping others, e.g. `Ema`, are not serializable.
+pub trait Checkpoint: Training + Sized {
+    /// Serializes this training together with `model`
+    fn save_checkpoint(&self, model: &Self::Model) -> Vec<u8>
+        where Self: Encodable,
+              Self::Model: Encodable
+    {
+        json::encode(&(self, model)).expect("training state must be encodable").into_bytes()
+    }
+
+    /// Restores a training and its model from the output of `save_checkpoint`
+    fn load_checkpoint(bytes: &[u8]) -> Result<(Self, Self::Model), Error>
+        where Self: Decodable,
+              Self::Model: Decodable
+    {
+        let text = ::std::str::from_utf8(bytes).map_err(|_| Error::InvalidCheckpoint)?;
+        json::decode(text).map_err(|_| Error::InvalidCheckpoint)
+    }
+}
+
+impl<T> Checkpoint for T where T: Training {}
@@ -1,4 +1,7 @@
 extern crate vikos;
+#[cfg(feature = "rand")]
+extern crate rand;
+extern crate rustc_serialize;
 
 use vikos::{cost, model, training, teacher};
 use std::default::Default;
@@ -181,7 +184,7 @@ fn linear_nesterov_2d() {
 #[test]
 fn logistic_sgd_2d_least_squares() {
 
-    use vikos::{learn_history, Model};
+    use vikos::learn_history;
 
     let history = [([2.7, 2.5], 0.0),
                    ([1.4, 2.3], 0.0),
@@ -206,7 +209,7 @@ fn logistic_sgd_2d_least_squares() {
     println!("{:?}", model);
 
     let classification_errors = history.iter()
-        .map(|&(input, truth)| model.predict(&input).round() == truth)
+        .map(|&(input, truth)| model.predict_class(&input, 0.5) == (truth == 1.0))
         .fold(0,
               |errors, correct| if correct { errors } else { errors + 1 });
 
@@ -215,7 +218,7 @@ fn logistic_sgd_2d_least_squares() {
 
 #[test]
 fn logistic_sgd_2d_max_likelihood() {
-    use vikos::{learn_history, Model};
+    use vikos::learn_history;
 
     let history = [([2.7, 2.5], 0.0),
                    ([1.4, 2.3], 0.0),
@@ -240,7 +243,7 @@ fn logistic_sgd_2d_max_likelihood() {
     println!("{:?}", model);
 
     let classification_errors = history.iter()
-        .map(|&(input, truth)| model.predict(&input).round() == truth)
+        .map(|&(input, truth)| model.predict_class(&input, 0.5) == (truth == 1.0))
         .fold(0,
               |errors, correct| if correct { errors } else { errors + 1 });
 
@@ -249,7 +252,7 @@ fn logistic_sgd_2d_max_likelihood() {
 
 #[test]
 fn logistic_sgd_2d_max_likelihood_bool() {
-    use vikos::{learn_history, Model};
+    use vikos::learn_history;
 
     let history = [([2.7, 2.5], false),
                    ([1.4, 2.3], false),
@@ -274,9 +277,3135 @@ fn logistic_sgd_2d_max_likelihood_bool() {
     println!("{:?}", model);
 
     let classification_errors = history.iter()
-        .map(|&(input, truth)| model.predict(&input).round() == if truth {1.0} else {0.0})
+        .map(|&(input, truth)| model.predict_class(&input, 0.5) == truth)
         .fold(0,
               |errors, correct| if correct { errors } else { errors + 1 });
 
     assert_eq!(0, classification_errors);
-}
\ No newline at end of file
+}
+#[test]
+fn validate_input_dimension() {
+    use vikos::{Model, Error};
+
+    let model = model::Linear {
+        m: vec![0.0, 0.0],
+        c: 0.0,
+    };
+
+    assert_eq!(Ok(()), model.validate(&vec![1.0, 2.0]));
+
+    let result = model.validate(&vec![1.0, 2.0, 3.0]);
+    assert_eq!(Err(Error::DimensionMismatch {
+                   expected: 2,
+                   actual: 3,
+               }),
+               result);
+    assert_eq!("input has dimension 3, but model expects dimension 2",
+               format!("{}", result.unwrap_err()));
+}
+
+#[test]
+fn ordinal_regression_thresholds() {
+
+    use vikos::{learn_history, Model};
+
+    // Ratings 0, 1 and 2 with class boundaries at x = -0.5 and x = 0.5
+    let history: Vec<_> = (0..13)
+        .map(|i| -1.5 + 0.25 * i as f64)
+        .filter(|&x: &f64| (x.abs() - 0.5).abs() > 0.01)
+        .map(|x| (x, if x < -0.5 { 0.0 } else if x < 0.5 { 1.0 } else { 2.0 }))
+        .collect();
+
+    let mut model = model::OrdinalRegression::new(0.0, 3);
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Momentum {
+        l0: 0.1,
+        t: 1000.0,
+        inertia: 0.9,
+    };
+
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(5000).cloned());
+
+    println!("{:?}, thresholds: {:?}", model, model.thresholds());
+
+    let thresholds = model.thresholds();
+    assert!(thresholds[0] < thresholds[1]);
+    // The decision boundaries are located between the neighbouring data points
+    assert!((thresholds[0] / model.m + 0.5).abs() < 0.25);
+    assert!((thresholds[1] / model.m - 0.5).abs() < 0.25);
+
+    for &(input, truth) in &history {
+        assert_eq!(truth, model.predict(&input).round());
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn gradient_descent_dropout_skips_updates() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [([1.0, 7.0], 18.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::GradientDescentDropout {
+        learning_rate: 0.0001,
+        dropout: 0.3,
+        seed: [1, 2, 3, 4],
+    };
+    let mut training = teacher.new_training(&model);
+
+    let mut skipped = 0;
+    for &(features, truth) in history.iter().cycle().take(3000) {
+        let before = model.clone();
+        training.teach_event(&cost, &mut model, &features, truth);
+        skipped += (0..2).filter(|&i| before.m[i] == model.m[i]).count();
+        if before.c == model.c {
+            skipped += 1;
+        }
+    }
+
+    // Three coefficents for each of the 3000 events
+    let fraction = skipped as f64 / 9000.0;
+    println!("fraction of skipped updates: {}", fraction);
+    assert!(fraction > 0.28);
+    assert!(fraction < 0.32);
+}
+
+#[test]
+fn teach_batch_matches_teach_event() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::GradientDescent { learning_rate: 0.01 };
+
+    let mut batched = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let mut training = teacher.new_training(&batched);
+    training.teach_batch(&cost, &mut batched, &history);
+
+    let mut looped = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let mut training = teacher.new_training(&looped);
+    for &(features, truth) in history.iter() {
+        training.teach_event(&cost, &mut looped, &features, truth);
+    }
+
+    assert_eq!(looped.m, batched.m);
+    assert_eq!(looped.c, batched.c);
+}
+
+#[test]
+fn decision_stump_fit() {
+
+    use vikos::Model;
+
+    // Only the second feature separates the targets
+    let history = [([0.0, 1.0, 5.0], 1.0),
+                   ([1.0, 2.0, 3.0], 1.0),
+                   ([0.0, 6.0, 4.0], 5.0),
+                   ([1.0, 7.0, 3.0], 5.0)];
+
+    let stump = model::DecisionStump::fit(&history, &[0, 1, 2]);
+
+    assert_eq!(1, stump.feature);
+    assert_eq!(4.0, stump.threshold);
+    for &(input, truth) in history.iter() {
+        assert_eq!(truth, stump.predict(&input));
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn decision_stump_random_feature_subset() {
+
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    let history = [([0.0, 1.0, 5.0], 1.0),
+                   ([1.0, 2.0, 3.0], 1.0),
+                   ([0.0, 6.0, 4.0], 5.0),
+                   ([1.0, 7.0, 3.0], 5.0)];
+
+    let mut chosen = [0; 3];
+    for seed in 1..100 {
+        // With a single candidate, the stump has to split on the feature drawn
+        let mut features = [0, 1, 2];
+        XorShiftRng::from_seed([seed, 2, 3, 4]).shuffle(&mut features);
+        let mut rng = XorShiftRng::from_seed([seed, 2, 3, 4]);
+        let stump = model::DecisionStump::fit_random_subset(&history, 1, &mut rng);
+        assert_eq!(features[0], stump.feature);
+        chosen[stump.feature] += 1;
+
+        // Considering every feature always yields the best split
+        let stump = model::DecisionStump::fit_random_subset(&history, 3, &mut rng);
+        assert_eq!(1, stump.feature);
+    }
+
+    // Every feature gets drawn for some seed
+    assert!(chosen.iter().all(|&n| n > 0));
+}
+
+#[test]
+fn logistic_predict_logit() {
+
+    use vikos::{learn_history, Model};
+
+    let history = [([2.7, 2.5], false),
+                   ([1.4, 2.3], false),
+                   ([3.3, 4.4], false),
+                   ([1.3, 1.8], false),
+                   ([3.0, 3.0], false),
+                   ([7.6, 2.7], true),
+                   ([5.3, 2.0], true),
+                   ([6.9, 1.7], true),
+                   ([8.6, -0.2], true),
+                   ([7.6, 3.5], true)];
+
+    let mut model = model::Logistic::default();
+    let teacher = teacher::GradientDescent { learning_rate: 0.3 };
+    let cost = cost::MaxLikelihood {};
+
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(20).cloned());
+
+    for &(input, _) in history.iter() {
+        let sigmoid = 1.0 / (1.0 + (-model.predict_logit(&input)).exp());
+        assert!((sigmoid - model.predict(&input)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn logistic_class_weighted_imbalanced() {
+
+    use vikos::{learn_history, Model};
+
+    let negatives = [[2.7, 2.5], [1.4, 2.3], [3.3, 4.4], [1.3, 1.8], [3.0, 3.0]];
+    let positive = [7.6, 2.7];
+    // Ten negative events for each positive one
+    let mut history: Vec<_> = negatives.iter().cycle().take(50).map(|&x| (x, false)).collect();
+    for i in 0..5 {
+        history.insert(i * 11, (positive, true));
+    }
+
+    let teacher = teacher::GradientDescent { learning_rate: 0.01 };
+    let classification_errors = |model: &model::Logistic<[f64; 2]>| {
+        history.iter()
+            .filter(|&&(input, truth)| (model.predict(&input) > 0.5) != truth)
+            .count()
+    };
+
+    let mut unweighted = model::Logistic::default();
+    learn_history(&teacher,
+                  &cost::MaxLikelihood {},
+                  &mut unweighted,
+                  history.iter().cycle().take(100).cloned());
+
+    let mut weighted = model::Logistic::default();
+    let cost = cost::ClassWeighted {
+        cost: cost::MaxLikelihood {},
+        positive: 10.0,
+        negative: 1.0,
+    };
+    learn_history(&teacher,
+                  &cost,
+                  &mut weighted,
+                  history.iter().cycle().take(100).cloned());
+
+    println!("unweighted: {:?}, weighted: {:?}", unweighted, weighted);
+    assert!(unweighted.predict(&positive) < 0.5);
+    assert!(weighted.predict(&positive) > 0.5);
+    assert_eq!(0, classification_errors(&weighted));
+}
+
+#[test]
+fn linear_to_equation() {
+
+    use vikos::learn_history;
+    use vikos::model::Describe;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Momentum {
+        l0: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(5000).cloned());
+
+    assert_eq!("y = 1.00*x0 + 2.00*x1 + 3.00", model.to_equation());
+
+    let model = model::Linear { m: -0.5, c: -1.0 };
+    assert_eq!("y = -0.50*x0 - 1.00", model.to_equation());
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn importance_sgd_converges_faster_than_uniform() {
+
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use vikos::{Cost, Model, Teacher, Training};
+
+    // Most events are explained easily, a few events with large features dominate the cost
+    let mut history: Vec<_> = (0..95).map(|i| (0.01 * (i % 5) as f64, 1.0)).collect();
+    history.extend((0..5).map(|i| (5.0 + i as f64, 1.0 + 2.0 * (5.0 + i as f64))));
+
+    let cost = cost::LeastSquares {};
+    let total_cost = |model: &model::Linear<f64>| -> f64 {
+        history.iter().map(|&(x, y)| cost.cost(model.predict(&x), y)).sum()
+    };
+
+    let start = model::Linear { m: 0.0, c: 0.0 };
+    let learning_rate = 0.0005;
+
+    let mut importance = start.clone();
+    let teacher = teacher::ImportanceSgd {
+        learning_rate: learning_rate,
+        seed: [1, 2, 3, 4],
+    };
+    let mut training = teacher.new_training(&importance);
+    for _ in 0..5 {
+        training.teach_batch(&cost, &mut importance, &history);
+    }
+
+    let mut uniform = start.clone();
+    let teacher = teacher::GradientDescent { learning_rate: learning_rate };
+    let mut training = teacher.new_training(&uniform);
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    for _ in 0..5 * history.len() {
+        let &(x, y) = rng.choose(&history).unwrap();
+        training.teach_event(&cost, &mut uniform, &x, y);
+    }
+
+    println!("importance: {:?} cost: {}, uniform: {:?} cost: {}",
+             importance,
+             total_cost(&importance),
+             uniform,
+             total_cost(&uniform));
+    assert!(total_cost(&importance) < total_cost(&uniform));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn importance_sgd_samples_by_current_batch() {
+
+    use vikos::{Teacher, Training};
+
+    let cost = cost::LeastSquares {};
+    // A learning rate of zero keeps the model, so the estimates equal the costs of the batch
+    let mut model = model::Linear { m: 1.0, c: 0.0 };
+    let mut training = teacher::ImportanceSgd {
+            learning_rate: 0.0,
+            seed: [1, 2, 3, 4],
+        }
+        .new_training(&model);
+
+    training.teach_batch(&cost, &mut model, &[(1.0, 1.0), (2.0, 2.0)]);
+    assert_eq!(vec![0.0, 0.0], training.costs);
+
+    // Second batch of the same size, but different events
+    training.teach_batch(&cost, &mut model, &[(1.0, 3.0), (2.0, 6.0)]);
+    assert_eq!(vec![4.0, 16.0], training.costs);
+}
+
+#[test]
+fn gradient_monitor_detects_dead_coefficent() {
+
+    use vikos::{Teacher, Training};
+
+    // The second feature is always zero
+    let history = [(vec![0.0, 0.0, 7.0], 17.0),
+                   (vec![1.0, 0.0, 2.0], 8.0),
+                   (vec![2.0, 0.0, -2.0], 1.0)];
+    let mut model = model::Linear {
+        m: vec![0.0, 0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+    let teacher =
+        teacher::GradientMonitor { teacher: teacher::GradientDescent { learning_rate: 0.01 } };
+    let mut training = teacher.new_training(&model);
+
+    for &(ref features, truth) in history.iter().cycle().take(300) {
+        training.teach_event(&cost, &mut model, features, truth);
+    }
+
+    let summary = training.summary();
+    println!("{:?}", summary);
+    assert_eq!(4, summary.len());
+    assert_eq!(300, summary[1].count);
+    assert_eq!(0.0, summary[1].max);
+    for i in [0, 2, 3].iter() {
+        assert!(summary[*i].max > 1.0);
+        assert!(summary[*i].mean > 0.0);
+    }
+}
+
+#[test]
+fn then_switches_within_batch() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0)];
+    let cost = cost::LeastSquares {};
+    // A learning rate of zero keeps the model, so only the last event changes it
+    let teacher = teacher::Then {
+        first: teacher::GradientDescent { learning_rate: 0.0 },
+        second: teacher::GradientDescent { learning_rate: 0.1 },
+        events: 2,
+    };
+    let mut model = model::Linear::default();
+    let mut training = teacher.new_training(&model);
+    training.teach_batch(&cost, &mut model, &history);
+    assert_eq!(0, training.remaining_events);
+
+    let mut expected = model::Linear::default();
+    let mut training = teacher::GradientDescent { learning_rate: 0.1 }.new_training(&expected);
+    training.teach_event(&cost, &mut expected, &2.0, 5.0);
+    assert_eq!(expected, model);
+}
+
+#[test]
+fn wrappers_forward_batches() {
+
+    use vikos::{Teacher, Training};
+
+    fn one_batch<T>(teacher: &T) -> model::Linear<[f64; 2]>
+        where T: Teacher<model::Linear<[f64; 2]>>
+    {
+        let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+        let mut model = model::Linear::default();
+        let mut training = teacher.new_training(&model);
+        training.teach_batch(&cost::LeastSquares {}, &mut model, &history);
+        model
+    }
+
+    let batch = || {
+        teacher::BatchGradientDescent {
+            learning_rate: 0.01,
+            reduction: training::Reduction::Sum,
+        }
+    };
+    // A single step with the summed gradient differs from three steps, one for each event
+    let expected = one_batch(&batch());
+    assert!(expected.m != one_batch(&teacher::GradientDescent { learning_rate: 0.01 }).m);
+
+    assert_eq!(expected, one_batch(&teacher::GradientMonitor { teacher: batch() }));
+    assert_eq!(expected,
+               one_batch(&teacher::Then {
+                   first: batch(),
+                   second: teacher::GradientDescent { learning_rate: 0.01 },
+                   events: 3,
+               }));
+    assert_eq!(expected,
+               one_batch(&teacher::RejectOutliers {
+                   teacher: batch(),
+                   k: 1000.0,
+                   warmup: 1,
+                   floor: 0.0,
+               }));
+    assert_eq!(expected,
+               one_batch(&teacher::Ema {
+                   teacher: batch(),
+                   decay: 0.9,
+               }));
+    assert_eq!(expected,
+               one_batch(&teacher::Constrained {
+                   teacher: batch(),
+                   lower: vec![-100.0; 3],
+                   upper: vec![100.0; 3],
+               }));
+    assert_eq!(expected,
+               one_batch(&teacher::FreezeCoefficents {
+                   teacher: batch(),
+                   frozen: vec![],
+               }));
+    assert_eq!(expected,
+               one_batch(&teacher::ClipGradientNorm {
+                   teacher: batch(),
+                   max_norm: 1000.0,
+               }));
+}
+
+#[test]
+fn softplus_positive_regression() {
+
+    use vikos::{learn_history, Model, Cost};
+
+    let history = [(0.0, 0.2), (1.0, 0.5), (2.0, 1.2), (3.0, 2.1), (4.0, 3.0), (5.0, 4.1)];
+
+    let mut model = model::Softplus::default();
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::GradientDescent { learning_rate: 0.01 };
+    let total_cost = |model: &model::Softplus<f64>| -> f64 {
+        history.iter().map(|&(x, y)| cost.cost(model.predict(&x), y)).sum()
+    };
+    let start_cost = total_cost(&model);
+
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(3000).cloned());
+
+    println!("{:?}, cost: {}", model, total_cost(&model));
+    assert!(total_cost(&model) < 0.1 * start_cost);
+    for x in -20..20 {
+        assert!(model.predict(&(x as f64)) > 0.0);
+    }
+}
+
+#[test]
+fn momentum_then_gradient_descent() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Then {
+        first: teacher::Momentum {
+            l0: 0.009,
+            t: 1000.0,
+            inertia: 0.995,
+        },
+        second: teacher::GradientDescent { learning_rate: 0.005 },
+        events: 1500,
+    };
+    let mut training = teacher.new_training(&model);
+
+    for &(features, truth) in history.iter().cycle().take(1500) {
+        training.teach_event(&cost, &mut model, &features, truth);
+    }
+    assert_eq!(0, training.remaining_events);
+    let after_first = model.clone();
+
+    for &(features, truth) in history.iter().cycle().take(3000) {
+        training.teach_event(&cost, &mut model, &features, truth);
+    }
+
+    println!("after momentum: {:?}, after gradient descent: {:?}", after_first, model);
+    assert!((model.c - 3.0).abs() < (after_first.c - 3.0).abs());
+    assert!(model.m[0] < 1.05);
+    assert!(model.m[0] > 0.95);
+    assert!(model.m[1] < 2.05);
+    assert!(model.m[1] > 1.95);
+    assert!(model.c < 3.05);
+    assert!(model.c > 2.95);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn ada_hessian_ill_conditioned() {
+
+    use vikos::{learn_history, Cost, Model};
+
+    // The second feature is a hundred times larger than the first one
+    let mut history = Vec::new();
+    for &a in [0.0, 0.5, 1.0].iter() {
+        for &b in [0.0, 50.0, 100.0].iter() {
+            history.push(([a, b], a + 0.02 * b + 0.5));
+        }
+    }
+    let cost = cost::LeastSquares {};
+    let total_cost = |model: &model::Linear<[f64; 2]>| -> f64 {
+        history.iter().map(|&(x, y)| cost.cost(model.predict(&x), y)).sum()
+    };
+    let start = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+
+    let mut ada_hessian = start.clone();
+    let teacher = teacher::AdaHessian {
+        learning_rate: 0.2,
+        decay: 0.99,
+        epsilon: 1e-8,
+        probe_interval: 1,
+        seed: [1, 2, 3, 4],
+    };
+    learn_history(&teacher,
+                  &cost,
+                  &mut ada_hessian,
+                  history.iter().cycle().take(2000).cloned());
+
+    // Any larger learning rate diverges, due to the large second feature
+    let mut gradient_descent = start.clone();
+    let teacher = teacher::GradientDescent { learning_rate: 0.0001 };
+    learn_history(&teacher,
+                  &cost,
+                  &mut gradient_descent,
+                  history.iter().cycle().take(2000).cloned());
+
+    println!("AdaHessian: {:?}, cost {}; GradientDescent: {:?}, cost {}",
+             ada_hessian,
+             total_cost(&ada_hessian),
+             gradient_descent,
+             total_cost(&gradient_descent));
+    assert!(total_cost(&ada_hessian) < 0.01 * total_cost(&gradient_descent));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+#[should_panic(expected = "probe_interval must be at least one")]
+fn ada_hessian_without_probes() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::AdaHessian {
+        learning_rate: 0.2,
+        decay: 0.99,
+        epsilon: 1e-8,
+        probe_interval: 0,
+        seed: [1, 2, 3, 4],
+    };
+    teacher.new_training(&model::Constant::<()>::new(0.0));
+}
+
+#[test]
+fn logistic_with_base_rate() {
+
+    use vikos::{Model, Teacher, Training};
+
+    let negatives = [[2.7, 2.5], [1.4, 2.3], [3.3, 4.4], [1.3, 1.8], [3.0, 3.0]];
+    let positives = [[7.6, 2.7], [5.3, 2.0], [6.9, 1.7], [8.6, -0.2], [7.6, 3.5]];
+    // Forty negative events for each positive one
+    let mut history = Vec::new();
+    for &positive in positives.iter() {
+        history.extend(negatives.iter().cycle().take(40).map(|&x| (x, false)));
+        history.push((positive, true));
+    }
+
+    let base_rate = 5.0 / 205.0;
+    let model = model::Logistic::with_base_rate([0.0; 2], base_rate);
+    assert!((model.predict(&[1.0, 1.0]) - base_rate).abs() < 1e-12);
+    // Weights with a dimension known only at runtime
+    let dynamic = model::Logistic::with_base_rate(vec![0.0; 2], base_rate);
+    assert!((dynamic.predict(&vec![1.0, 1.0]) - base_rate).abs() < 1e-12);
+
+    let epochs_until_no_errors = |mut model: model::Logistic<[f64; 2]>| {
+        let teacher = teacher::GradientDescent { learning_rate: 0.02 };
+        let cost = cost::MaxLikelihood {};
+        let mut training = teacher.new_training(&model);
+        for epoch in 1..1000 {
+            for &(input, truth) in history.iter() {
+                training.teach_event(&cost, &mut model, &input, truth);
+            }
+            if history.iter().all(|&(input, truth)| (model.predict(&input) > 0.5) == truth) {
+                return epoch;
+            }
+        }
+        panic!("no convergence")
+    };
+
+    let base_rate_epochs = epochs_until_no_errors(model);
+    let zero_epochs = epochs_until_no_errors(model::Logistic::default());
+    println!("base rate: {} epochs, zero: {} epochs", base_rate_epochs, zero_epochs);
+    assert!(base_rate_epochs < zero_epochs);
+}
+
+#[test]
+#[should_panic(expected = "base rate must be between 0 and 1")]
+fn logistic_with_certain_base_rate() {
+    model::Logistic::with_base_rate(0.0, 1.0);
+}
+
+#[test]
+fn eval_predictions() {
+
+    use vikos::{learn_history, eval, Model};
+
+    let history = [([2.7, 2.5], false),
+                   ([1.4, 2.3], false),
+                   ([3.3, 4.4], false),
+                   ([1.3, 1.8], false),
+                   ([3.0, 3.0], false),
+                   ([7.6, 2.7], true),
+                   ([5.3, 2.0], true),
+                   ([6.9, 1.7], true),
+                   ([8.6, -0.2], true),
+                   ([7.6, 3.5], true)];
+
+    let mut model = model::Logistic::default();
+    let teacher = teacher::GradientDescent { learning_rate: 0.3 };
+    let cost = cost::MaxLikelihood {};
+
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(20).cloned());
+
+    let pairs: Vec<_> = eval::predictions(&model, history.iter().cloned()).collect();
+    assert_eq!(history.len(), pairs.len());
+    for (&(prediction, truth), &(input, expected_truth)) in pairs.iter().zip(history.iter()) {
+        assert_eq!(model.predict(&input), prediction);
+        assert_eq!(expected_truth, truth);
+    }
+}
+
+#[test]
+fn ridge_regularization_path() {
+
+    use vikos::model_selection::regularization_path;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let start = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+    let lambdas = [0.0, 0.1, 1.0, 10.0];
+
+    let path = regularization_path(|lambda| {
+                                       teacher::Ridge {
+                                           learning_rate: 0.005,
+                                           lambda: lambda,
+                                       }
+                                   },
+                                   &cost,
+                                   &start,
+                                   &history,
+                                   2000,
+                                   &lambdas);
+
+    let norms: Vec<f64> = path.iter()
+        .map(|&(_, ref model)| model.m[0].powi(2) + model.m[1].powi(2) + model.c.powi(2))
+        .collect();
+    println!("{:?}", norms);
+
+    assert_eq!(lambdas.len(), path.len());
+    for (&(lambda, _), &expected) in path.iter().zip(lambdas.iter()) {
+        assert_eq!(expected, lambda);
+    }
+    for pair in norms.windows(2) {
+        assert!(pair[1] < pair[0]);
+    }
+}
+
+#[test]
+fn constant_closed_form() {
+
+    use std::iter::repeat;
+
+    let history = [1.0, 3.0, 4.0, 7.0, 8.0, 11.0, 29.0];
+
+    let mean = model::Constant::fit_mean(repeat(()).zip(history.iter().cloned()));
+    assert_eq!(9.0, mean.c);
+
+    let median = model::Constant::fit_median(repeat(()).zip(history.iter().cloned()));
+    assert_eq!(7.0, median.c);
+
+    let even = [4.0, 1.0, 3.0, 8.0];
+    let median = model::Constant::<()>::fit_median(repeat(()).zip(even.iter().cloned()));
+    assert_eq!(3.5, median.c);
+
+    // Without any truth there is nothing to fit
+    assert!(model::Constant::<()>::fit_mean(Vec::new()).c.is_nan());
+    assert!(model::Constant::<()>::fit_median(Vec::new()).c.is_nan());
+}
+
+#[test]
+fn constant_baseline_learn_history() {
+
+    use std::iter::repeat;
+    use vikos::learn_history;
+
+    let history = [1.0, 3.0, 4.0, 7.0, 8.0, 11.0, 29.0];
+
+    let mut model = model::Constant::new(0.0);
+    let teacher = teacher::GradientDescentAl { l0: 0.3, t: 4.0 };
+    learn_history(&teacher,
+                  &cost::LeastSquares {},
+                  &mut model,
+                  repeat(()).zip(history.iter().cycle().take(100).cloned()));
+
+    let mean = model::Constant::<()>::fit_mean(repeat(()).zip(history.iter().cloned()));
+    assert!((model.c - mean.c).abs() < 0.1);
+}
+
+// Targets are always `f64`, so no type annotations are required for float literals
+#[test]
+fn linear_unannotated_literals() {
+
+    use vikos::learn_history;
+
+    let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0)];
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+
+    learn_history(&teacher::GradientDescent { learning_rate: 0.2 },
+                  &cost::LeastSquares {},
+                  &mut model,
+                  history.iter().cycle().take(20).cloned());
+
+    assert!((model.m - 1.0).abs() < 0.1);
+    assert!((model.c - 3.0).abs() < 0.1);
+}
+
+#[test]
+fn full_gradient_matches_per_coefficent_gradient() {
+
+    use vikos::{full_gradient, Cost, Model};
+
+    let mut model = model::Logistic::default();
+    *model.coefficent(0) = 0.3;
+    *model.coefficent(1) = -0.2;
+    *model.coefficent(2) = 0.1;
+    let cost = cost::MaxLikelihood {};
+    let features = [2.7, 2.5];
+
+    let gradient = full_gradient(&cost, &model, &features, true);
+
+    let prediction = model.predict(&features);
+    assert_eq!(model.num_coefficents(), gradient.len());
+    for (ci, &g) in gradient.iter().enumerate() {
+        assert_eq!(cost.gradient(prediction, true, model.gradient(ci, &features)), g);
+    }
+}
+
+#[test]
+fn truncated_gradient_sparse() {
+
+    // Only the first feature is relevant, the other two are noise
+    let history: Vec<_> = (0..20000)
+        .map(|i| {
+            let i = i as f64;
+            let x = (i * 0.7).sin();
+            ([x, (i * 1.3).cos(), (i * 2.9).sin()], 2.0 * x)
+        })
+        .collect();
+
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear {
+        m: [0.0, 0.0, 0.0],
+        c: 0.0,
+    };
+    let teacher = teacher::TruncatedGradient {
+        learning_rate: 0.01,
+        gravity: 0.05,
+        theta: std::f64::INFINITY,
+        period: 10,
+    };
+
+    vikos::learn_history(&teacher, &cost, &mut model, history.iter().cloned());
+    println!("{:?}", model);
+
+    assert!(model.m[0] > 1.5);
+    assert_eq!(0.0, model.m[1]);
+    assert_eq!(0.0, model.m[2]);
+    assert_eq!(0.0, model.c);
+}
+
+#[test]
+#[should_panic(expected = "period must be at least one")]
+fn truncated_gradient_without_period() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::TruncatedGradient {
+        learning_rate: 0.01,
+        gravity: 0.05,
+        theta: std::f64::INFINITY,
+        period: 0,
+    };
+    teacher.new_training(&model::Constant::<()>::new(0.0));
+}
+
+#[test]
+fn linear_input_gradient() {
+
+    let model = model::Linear {
+        m: vec![1.5, -2.0, 0.5],
+        c: 3.0,
+    };
+
+    assert_eq!(model.m, model.input_gradient(&vec![4.0, 1.0, -7.0]));
+}
+
+#[test]
+fn tune_batch_size_picks_candidate() {
+
+    use vikos::model_selection::tune_batch_size;
+
+    let sample: Vec<_> = (0..1000).map(|i| (i as f64, 2.0 * i as f64 + 1.0)).collect();
+    let candidates = [1, 10, 100];
+
+    let batch_size = tune_batch_size(&teacher::GradientDescent { learning_rate: 0.0001 },
+                                     &cost::LeastSquares {},
+                                     &model::Linear { m: 0.0, c: 0.0 },
+                                     &sample,
+                                     &candidates);
+
+    assert!(candidates.contains(&batch_size));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn train_test_split_disjoint() {
+
+    use vikos::model_selection::train_test_split;
+
+    let data: Vec<usize> = (0..100).collect();
+    let (train, test) = train_test_split(&data, 0.2, [1, 2, 3, 4]);
+
+    assert_eq!(80, train.len());
+    assert_eq!(20, test.len());
+    assert!(test.iter().all(|x| !train.contains(x)));
+    assert_eq!((train.clone(), test.clone()),
+               train_test_split(&data, 0.2, [1, 2, 3, 4]));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+#[should_panic(expected = "test_fraction must be between 0 and 1")]
+fn train_test_split_too_large_fraction() {
+
+    use vikos::model_selection::train_test_split;
+
+    let data: Vec<usize> = (0..100).collect();
+    train_test_split(&data, 1.5, [1, 2, 3, 4]);
+}
+
+#[test]
+fn linear_fit_exact_matches_sgd() {
+
+    use vikos::learn_history;
+    use vikos::model::ClosedForm;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+
+    let exact = model::Linear::fit_exact(history.iter().cloned());
+
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let teacher = teacher::Momentum {
+        l0: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+    learn_history(&teacher,
+                  &cost::LeastSquares {},
+                  &mut model,
+                  history.iter().cycle().take(1500).cloned());
+
+    println!("{:?} {:?}", exact, model);
+    assert!((exact.m[0] - 1.0).abs() < 1e-9);
+    assert!((exact.m[1] - 2.0).abs() < 1e-9);
+    assert!((exact.c - 3.0).abs() < 1e-9);
+    assert!((exact.m[0] - model.m[0]).abs() < 0.1);
+    assert!((exact.m[1] - model.m[1]).abs() < 0.1);
+    assert!((exact.c - model.c).abs() < 0.1);
+}
+
+#[test]
+fn learn_epochs_report() {
+
+    use vikos::{learn_epochs, StopReason};
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::GradientDescent { learning_rate: 0.01 };
+
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let report = learn_epochs(&teacher, &cost, &mut model, &history, 10000, 1e-6);
+    println!("{:?}", report);
+    assert!(report.converged);
+    assert_eq!(StopReason::Converged, report.stopped_reason);
+    assert!(report.epochs_run < 10000);
+    assert!(report.final_cost < 0.01);
+
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let report = learn_epochs(&teacher, &cost, &mut model, &history, 5, 1e-9);
+    assert!(!report.converged);
+    assert_eq!(StopReason::MaxEpochs, report.stopped_reason);
+    assert_eq!(5, report.epochs_run);
+}
+
+#[test]
+fn grid_search_learning_rate() {
+
+    use vikos::learn_history;
+    use vikos::model_selection::grid_search;
+
+    let data: Vec<_> = (0..20)
+        .map(|i| {
+            let x = i as f64 / 20.0;
+            (x, 2.0 * x + 3.0)
+        })
+        .collect();
+    let grid = [(0.0001, 10), (0.0001, 100), (0.1, 10), (0.1, 100)];
+    let cost = cost::LeastSquares {};
+
+    let (best, score) = grid_search(&grid, &cost, &data, 4, |&(learning_rate, epochs), events| {
+        let mut model = model::Linear { m: 0.0, c: 0.0 };
+        learn_history(&teacher::GradientDescent { learning_rate: learning_rate },
+                      &cost,
+                      &mut model,
+                      events.iter().cycle().take(epochs * events.len()).cloned());
+        model
+    });
+
+    println!("{:?} {}", best, score);
+    assert_eq!((0.1, 100), best);
+}
+
+#[test]
+#[should_panic(expected = "cross validation requires at least two folds")]
+fn cross_validate_single_fold() {
+
+    use vikos::model_selection::cross_validate;
+
+    let data = [(0.0, 3.0), (1.0, 5.0)];
+    cross_validate(&cost::LeastSquares {}, &data, 1, |_| model::Linear { m: 2.0, c: 3.0 });
+}
+
+#[test]
+fn constrained_non_negative() {
+
+    use vikos::{Teacher, Training};
+
+    // Unconstrained, the best fit would be `y = -1 * x0 + 2 * x1`
+    let history = [([1.0, 0.0], -1.0), ([0.0, 1.0], 2.0), ([1.0, 1.0], 1.0)];
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let teacher = teacher::Constrained {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        lower: vec![0.0; 3],
+        upper: vec![std::f64::INFINITY; 3],
+    };
+
+    let mut training = teacher.new_training(&model);
+    for &(ref features, truth) in history.iter().cycle().take(3000) {
+        training.teach_event(&cost, &mut model, features, truth);
+        assert!(model.m[0] >= 0.0 && model.m[1] >= 0.0 && model.c >= 0.0);
+    }
+    println!("{:?}", model);
+
+    assert_eq!(0.0, model.m[0]);
+}
+
+#[test]
+#[should_panic(expected = "one lower and one upper bound for each of the 2 coefficents")]
+fn constrained_bounds_missing_offset() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::Constrained {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        lower: vec![0.0],
+        upper: vec![1.0],
+    };
+    teacher.new_training(&model::Linear { m: 0.0, c: 0.0 });
+}
+
+#[test]
+#[should_panic(expected = "lower bound 2 of coefficent 1 exceeds its upper bound 1")]
+fn constrained_empty_box() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::Constrained {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        lower: vec![0.0, 2.0],
+        upper: vec![1.0, 1.0],
+    };
+    teacher.new_training(&model::Linear { m: 0.0, c: 0.0 });
+}
+
+#[test]
+fn partial_fit_matches_concatenated_history() {
+
+    use vikos::{learn_history, Teacher, Training};
+
+    let history: Vec<_> = (0..90).map(|i| (i as f64 / 90.0, 2.0 * i as f64 / 90.0 + 3.0)).collect();
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Momentum {
+        l0: 0.1,
+        t: 100.0,
+        inertia: 0.9,
+    };
+
+    let mut concatenated = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher, &cost, &mut concatenated, history.iter().cloned());
+
+    let mut incremental = model::Linear { m: 0.0, c: 0.0 };
+    let mut training = teacher.new_training(&incremental);
+    for batch in history.chunks(25) {
+        training.partial_fit(&cost, &mut incremental, batch);
+    }
+
+    assert_eq!(concatenated.m, incremental.m);
+    assert_eq!(concatenated.c, incremental.c);
+}
+
+#[test]
+fn log_target_transform() {
+
+    use vikos::mean_cost;
+    use vikos::model::ClosedForm;
+
+    let history: Vec<_> = (0..30)
+        .map(|i| {
+            let x = i as f64 / 10.0;
+            (x, (x + 0.5).exp())
+        })
+        .collect();
+    let cost = cost::LeastSquares {};
+
+    let raw = model::Linear::fit_exact(history.iter().cloned());
+
+    let mut transformed = model::TargetTransform::log(model::Linear { m: 0.0, c: 0.0 });
+    transformed.learn_transformed(&teacher::GradientDescent { learning_rate: 0.1 },
+                                  &cost,
+                                  history.iter().cycle().take(3000).cloned());
+
+    println!("{:?}", transformed.model);
+    assert!((transformed.model.m - 1.0).abs() < 0.01);
+    assert!((transformed.model.c - 0.5).abs() < 0.01);
+    assert!(mean_cost(&cost, &transformed, &history) < mean_cost(&cost, &raw, &history));
+}
+
+#[test]
+fn coefficent_and_gradient_norm() {
+
+    use vikos::{coefficent_norm, gradient_norm};
+
+    let model = model::Linear {
+        m: [3.0, 0.0],
+        c: 4.0,
+    };
+    assert_eq!(5.0, coefficent_norm(&model));
+
+    // prediction is 10, so the outer derivative is 0.5 and the gradient [1, 1, 0.5]
+    assert_eq!(1.5,
+               gradient_norm(&cost::LeastSquares {}, &model, &[2.0, 2.0], 9.75));
+}
+
+#[test]
+fn dyn_model_collection() {
+
+    use vikos::DynModel;
+
+    let models: Vec<Box<dyn DynModel<[f64; 2]>>> = vec![Box::new(model::Linear {
+                                                             m: [1.0, 2.0],
+                                                             c: 3.0,
+                                                         }),
+                                                         Box::new(model::Constant::new(4.0))];
+
+    let predictions: Vec<f64> = models.iter().map(|m| m.predict_dyn(&[1.0, 1.0])).collect();
+    assert_eq!(vec![6.0, 4.0], predictions);
+}
+
+#[test]
+fn stacking_beats_base_models() {
+
+    use vikos::{learn_history, DynModel};
+    use vikos::model_selection::fit_stacking;
+
+    let data: Vec<_> = (0..25)
+        .map(|i| {
+            let x = [(i % 5) as f64 / 4.0, (i / 5) as f64 / 4.0];
+            (x, x[0] + x[1])
+        })
+        .collect();
+    let cost = cost::LeastSquares {};
+
+    // Each base model only sees one of the features
+    fn train_on(feature: usize, events: &[([f64; 2], f64)]) -> Box<dyn DynModel<[f64; 2]>> {
+        let mut model = model::Linear {
+            m: [0.0, 0.0],
+            c: 0.0,
+        };
+        let masked = events.iter().map(|&(x, y)| {
+            let mut masked = [0.0, 0.0];
+            masked[feature] = x[feature];
+            (masked, y)
+        });
+        let masked: Vec<_> = masked.collect();
+        learn_history(&teacher::GradientDescent { learning_rate: 0.05 },
+                      &cost::LeastSquares {},
+                      &mut model,
+                      masked.iter().cycle().take(200 * masked.len()).cloned());
+        Box::new(model)
+    }
+    let base_trainers: Vec<Box<dyn Fn(&[([f64; 2], f64)]) -> Box<dyn DynModel<[f64; 2]>>>> =
+        vec![Box::new(|events| train_on(0, events)), Box::new(|events| train_on(1, events))];
+
+    let stacking = fit_stacking(&base_trainers,
+                                &teacher::GradientDescent { learning_rate: 0.05 },
+                                &cost,
+                                &data,
+                                5,
+                                500);
+
+    let mse = |model: &dyn DynModel<[f64; 2]>| {
+        data.iter().map(|&(ref x, y)| (model.predict_dyn(x) - y).powi(2)).sum::<f64>() / 25.0
+    };
+    let stacking_error = mse(&stacking);
+    println!("{} {} {}",
+             stacking_error,
+             mse(&*stacking.base[0]),
+             mse(&*stacking.base[1]));
+    assert!(stacking_error < mse(&*stacking.base[0]));
+    assert!(stacking_error < mse(&*stacking.base[1]));
+}
+
+#[test]
+fn lr_find_diverges() {
+
+    use vikos::model_selection::lr_find;
+
+    let data: Vec<_> = (0..20).map(|i| (i as f64 / 20.0, 2.0 * i as f64 / 20.0 + 3.0)).collect();
+
+    let curve = lr_find(|rate| teacher::GradientDescent { learning_rate: rate },
+                        &cost::LeastSquares {},
+                        &model::Linear { m: 0.0, c: 0.0 },
+                        &data,
+                        1e-4,
+                        10.0,
+                        30);
+    println!("{:?}", curve);
+
+    assert!(curve.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    let (first_loss, last_loss) = (curve[0].1, curve[curve.len() - 1].1);
+    let min_loss = curve.iter().map(|&(_, loss)| loss).fold(std::f64::INFINITY, f64::min);
+    assert!(min_loss < first_loss);
+    // Diverged, the loss may even be infinite or NaN
+    assert!(!(last_loss < first_loss));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn logistic_sample_rate() {
+
+    use rand::{SeedableRng, XorShiftRng};
+    use vikos::Model;
+
+    let model: model::Logistic<[f64; 2]> = model::Logistic::with_base_rate([0.0; 2], 0.3);
+    let input = [1.0, 2.0];
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+
+    let n = 10000;
+    let positives: f64 = (0..n).map(|_| model.sample(&input, &mut rng)).sum();
+
+    assert!((positives / n as f64 - model.predict(&input)).abs() < 0.02);
+}
+
+#[test]
+fn closure_model_quadratic() {
+
+    use vikos::learn_history;
+
+    let history: Vec<_> = (0..21)
+        .map(|i| {
+            let x = i as f64 / 10.0 - 1.0;
+            (x, 2.0 * x * x - x + 1.0)
+        })
+        .collect();
+
+    // y = a * x^2 + b * x + c
+    let mut model = model::ClosureModel::new(vec![0.0; 3],
+                                             |w: &[f64], &x: &f64| w[0] * x * x + w[1] * x + w[2],
+                                             |_: &[f64], ci, &x: &f64| [x * x, x, 1.0][ci]);
+
+    learn_history(&teacher::GradientDescent { learning_rate: 0.1 },
+                  &cost::LeastSquares {},
+                  &mut model,
+                  history.iter().cycle().take(10000).cloned());
+
+    println!("{:?}", model.coefficents);
+    assert!((model.coefficents[0] - 2.0).abs() < 0.01);
+    assert!((model.coefficents[1] + 1.0).abs() < 0.01);
+    assert!((model.coefficents[2] - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn reject_outliers_constant() {
+
+    use vikos::{Teacher, Training};
+
+    // Clean events scatter around 10, every tenth event after the warmup is a gross outlier
+    let history: Vec<_> = (0..3000)
+        .map(|i| {
+            if i >= 500 && i % 10 == 0 {
+                ((), 1000.0)
+            } else {
+                ((), 10.0 + (i as f64).sin())
+            }
+        })
+        .collect();
+    let outliers = history.iter().filter(|&&(_, truth)| truth == 1000.0).count();
+
+    let cost = cost::LeastSquares {};
+    let mut model = model::Constant::new(0.0);
+    let teacher = teacher::RejectOutliers {
+        teacher: teacher::GradientDescent { learning_rate: 0.01 },
+        k: 3.0,
+        warmup: 300,
+        // `1e-3` times the magnitude of the truths
+        floor: 0.01,
+    };
+    let mut training = teacher.new_training(&model);
+    training.teach_batch(&cost, &mut model, &history);
+    println!("{:?} skipped: {}", model, training.skipped);
+
+    assert_eq!(outliers, training.skipped);
+    assert!((model.c - 10.0).abs() < 0.1);
+}
+
+#[test]
+fn reject_outliers_perfect_warmup() {
+
+    use vikos::{Teacher, Training};
+
+    // The warmup is fitted perfectly, so the median absolute residual starts at zero
+    let cost = cost::LeastSquares {};
+    let mut model = model::Constant::new(10.0);
+    let teacher = teacher::RejectOutliers {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        k: 3.0,
+        warmup: 1,
+        floor: 0.5,
+    };
+    let mut training = teacher.new_training(&model);
+    training.teach_event(&cost, &mut model, &(), 10.0);
+    training.teach_event(&cost, &mut model, &(), 11.0);
+
+    assert_eq!(0, training.skipped);
+    assert!(model.c > 10.0);
+}
+
+#[test]
+#[should_panic(expected = "warmup must be at least one event")]
+fn reject_outliers_without_warmup() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::RejectOutliers {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        k: 3.0,
+        warmup: 0,
+        floor: 0.5,
+    };
+    teacher.new_training(&model::Constant::<()>::new(0.0));
+}
+
+#[test]
+fn last_update_equals_coefficent_change() {
+
+    use vikos::{Teacher, Training, Model};
+
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear {
+        m: [0.5, -1.0],
+        c: 0.0,
+    };
+    let teacher = teacher::Nesterov {
+        l0: 0.1,
+        t: 100.0,
+        inertia: 0.9,
+    };
+    let mut training = teacher.new_training(&model);
+
+    for &(ref x, y) in [([1.0, 2.0], 3.0), ([2.0, 0.5], 1.0), ([0.0, 1.0], 2.0)].iter() {
+        let mut before = model.clone();
+        training.teach_event(&cost, &mut model, x, y);
+        for ci in 0..model.num_coefficents() {
+            let change = *model.coefficent(ci) - *before.coefficent(ci);
+            assert!((change - training.last_update()[ci]).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn log_loss_decreases() {
+
+    use vikos::{Cost, Teacher, Training, mean_cost};
+
+    // Classes alternate, so no epoch is dominated by a single class
+    let history = [([2.7, 2.5], false),
+                   ([7.6, 2.7], true),
+                   ([1.4, 2.3], false),
+                   ([5.3, 2.0], true),
+                   ([3.3, 4.4], false),
+                   ([6.9, 1.7], true),
+                   ([1.3, 1.8], false),
+                   ([8.6, -0.2], true),
+                   ([3.0, 3.0], false),
+                   ([7.6, 3.5], true)];
+
+    let cost = cost::MaxLikelihood {};
+    let mut model = model::Logistic::default();
+    let mut training = teacher::GradientDescent { learning_rate: 0.05 }.new_training(&model);
+
+    let mut log_loss = vec![mean_cost(&cost, &model, &history)];
+    for _ in 0..5 {
+        training.teach_batch(&cost, &mut model, &history);
+        log_loss.push(mean_cost(&cost, &model, &history));
+    }
+    println!("{:?}", log_loss);
+
+    assert!(log_loss.windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(cost.cost(0.0, true).is_finite());
+}
+
+#[test]
+fn minimize_quadratic() {
+
+    use vikos::minimize;
+
+    // Minimum at (1, -2)
+    let objective = |x: &[f64]| (x[0] - 1.0).powi(2) + 3.0 * (x[1] + 2.0).powi(2);
+    let gradient = |x: &[f64]| vec![2.0 * (x[0] - 1.0), 6.0 * (x[1] + 2.0)];
+
+    let x = minimize(objective, gradient, &[0.0, 0.0], 0.1, 200);
+
+    assert!((x[0] - 1.0).abs() < 1e-6);
+    assert!((x[1] + 2.0).abs() < 1e-6);
+}
+
+#[test]
+fn forward_selection_leaves_out_irrelevant_feature() {
+
+    use vikos::model_selection::forward_selection;
+
+    // Only the features 0 and 2 are relevant
+    let events = |offset: usize| -> Vec<(Vec<f64>, f64)> {
+        (offset..offset + 50)
+            .map(|i| {
+                let i = i as f64;
+                let x = vec![(i * 0.7).sin(), (i * 1.3).cos(), (i * 2.9).sin()];
+                let y = 2.0 * x[0] + 3.0 * x[2] + 1.0;
+                (x, y)
+            })
+            .collect()
+    };
+
+    let (selected, model) = forward_selection(&teacher::GradientDescent { learning_rate: 0.05 },
+                                              &cost::LeastSquares {},
+                                              &events(0),
+                                              &events(50),
+                                              100,
+                                              1e-3);
+    println!("{:?} {:?}", selected, model);
+
+    assert_eq!(vec![2, 0], selected);
+    assert_eq!(2, model.m.len());
+}
+
+#[test]
+fn resume_from_checkpoint() {
+
+    use vikos::{Teacher, Training};
+    use vikos::training::Checkpoint;
+
+    let history: Vec<_> = (0..200)
+        .map(|i| {
+            let x = vec![(i as f64 * 0.7).sin(), (i as f64 * 1.3).cos()];
+            let y = 2.0 * x[0] - x[1] + 0.5;
+            (x, y)
+        })
+        .collect();
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Momentum {
+        l0: 0.05,
+        t: 100.0,
+        inertia: 0.9,
+    };
+    let start = model::Linear {
+        m: vec![0.0, 0.0],
+        c: 0.0,
+    };
+
+    let mut uninterrupted = start.clone();
+    let mut training = teacher.new_training(&uninterrupted);
+    training.teach_batch(&cost, &mut uninterrupted, &history);
+
+    let mut model = start.clone();
+    let mut training = teacher.new_training(&model);
+    training.teach_batch(&cost, &mut model, &history[..100]);
+    let checkpoint = training.save_checkpoint(&model);
+
+    type Resumed = training::Momentum<model::Linear<Vec<f64>>>;
+    let (mut training, mut model) = Resumed::load_checkpoint(&checkpoint).unwrap();
+    training.teach_batch(&cost, &mut model, &history[100..]);
+
+    // JSON may round the last digit of the coefficents
+    for (a, b) in uninterrupted.m.iter().zip(model.m.iter()) {
+        assert!((a - b).abs() < 1e-12);
+    }
+    assert!((uninterrupted.c - model.c).abs() < 1e-12);
+    assert!(Resumed::load_checkpoint(b"garbage").is_err());
+}
+
+#[test]
+fn resume_lbfgs_from_checkpoint() {
+
+    use vikos::{Teacher, Training};
+    use vikos::training::Checkpoint;
+
+    let history = [([0.0, 0.0], 3.0), ([1.0, 0.0], 4.0), ([0.0, 1.0], 5.0), ([1.0, 1.0], 6.0)];
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::Lbfgs { memory: 5 };
+
+    let mut uninterrupted = model::Linear::default();
+    let mut training = teacher.new_training(&uninterrupted);
+    for _ in 0..6 {
+        training.teach_batch(&cost, &mut uninterrupted, &history);
+    }
+
+    let mut model = model::Linear::default();
+    let mut training = teacher.new_training(&model);
+    for _ in 0..3 {
+        training.teach_batch(&cost, &mut model, &history);
+    }
+    let checkpoint = training.save_checkpoint(&model);
+
+    type Resumed = training::Lbfgs<model::Linear<[f64; 2]>>;
+    let (mut training, mut model) = Resumed::load_checkpoint(&checkpoint).unwrap();
+    // The curvature history survived the round trip
+    assert!(!training.coefficent_changes.is_empty());
+    for _ in 0..3 {
+        training.teach_batch(&cost, &mut model, &history);
+    }
+
+    for (a, b) in uninterrupted.m.iter().zip(model.m.iter()) {
+        assert!((a - b).abs() < 1e-9);
+    }
+    assert!((uninterrupted.c - model.c).abs() < 1e-9);
+}
+
+#[test]
+fn checkpoint_round_trip() {
+
+    use vikos::{Teacher, Training};
+    use vikos::training::Checkpoint;
+    use rustc_serialize::{Encodable, Decodable};
+
+    type M = model::Linear<[f64; 2]>;
+
+    // A restored training continues like the original one
+    fn round_trip<T>(teacher: &T)
+        where T: Teacher<M>,
+              T::Training: Encodable + Decodable
+    {
+        let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+        let cost = cost::LeastSquares {};
+        let mut model = M::default();
+        let mut training = teacher.new_training(&model);
+        // Restore once before and once after learning. JSON may round the last digit of a
+        // float, so the models are compared with a tolerance.
+        for _ in 0..2 {
+            let checkpoint = training.save_checkpoint(&model);
+            let (mut restored, mut restored_model) = T::Training::load_checkpoint(&checkpoint)
+                .unwrap();
+            for _ in 0..3 {
+                training.teach_batch(&cost, &mut model, &history);
+                restored.teach_batch(&cost, &mut restored_model, &history);
+            }
+            for (a, b) in model.m.iter().zip(restored_model.m.iter()) {
+                assert!((a - b).abs() < 1e-9);
+            }
+            assert!((model.c - restored_model.c).abs() < 1e-9);
+        }
+    }
+
+    round_trip(&teacher::GradientDescent { learning_rate: 0.01 });
+    round_trip(&teacher::Ridge {
+        learning_rate: 0.01,
+        lambda: 0.1,
+    });
+    round_trip(&teacher::TruncatedGradient {
+        learning_rate: 0.01,
+        gravity: 0.05,
+        theta: std::f64::INFINITY,
+        period: 2,
+    });
+    round_trip(&teacher::Lbfgs { memory: 5 });
+    round_trip(&teacher::Rprop {});
+    round_trip(&teacher::GradientDescentAl { l0: 0.01, t: 10.0 });
+    round_trip(&teacher::BatchGradientDescent {
+        learning_rate: 0.01,
+        reduction: training::Reduction::Mean,
+    });
+    round_trip(&teacher::BoldDriver {
+        learning_rate: 0.01,
+        increase: 1.05,
+        decrease: 0.5,
+        min_learning_rate: 1e-6,
+        max_learning_rate: 1.0,
+    });
+    round_trip(&teacher::NormalizedGradientDescent { learning_rate: 0.1 });
+    round_trip(&teacher::CyclicalLr {
+        base: 0.001,
+        max: 0.01,
+        step_size: 4.0,
+    });
+    round_trip(&teacher::Sgdr {
+        t0: 4.0,
+        t_mult: 2.0,
+        lr_max: 0.01,
+        lr_min: 0.001,
+    });
+    round_trip(&teacher::Momentum {
+        l0: 0.01,
+        t: 100.0,
+        inertia: 0.9,
+    });
+    round_trip(&teacher::MomentumReset {
+        momentum: teacher::Momentum {
+            l0: 0.01,
+            t: 100.0,
+            inertia: 0.9,
+        },
+        patience: 3,
+    });
+    round_trip(&teacher::Nesterov {
+        l0: 0.01,
+        t: 100.0,
+        inertia: 0.9,
+    });
+}
+
+#[test]
+#[should_panic]
+fn assert_close_fails_outside_tolerance() {
+
+    vikos::testing::assert_close(1.1, 1.0, 0.05);
+}
+
+#[test]
+fn close_linear_models() {
+
+    use vikos::testing::{assert_close, model_close};
+
+    let a = model::Linear {
+        m: [1.0, 2.0],
+        c: 3.0,
+    };
+    let b = model::Linear {
+        m: [1.001, 1.999],
+        c: 3.0005,
+    };
+
+    assert_close(b.m[0], a.m[0], 0.01);
+    assert!(model_close(&a, &b, 0.01));
+    assert!(!model_close(&a, &b, 0.0001));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn gradient_noise_decays() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [(-1.0, -1.0), (0.0, 1.0), (1.0, 3.0), (2.0, 5.0)];
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    let teacher = teacher::GradientNoise {
+        learning_rate: 0.05,
+        noise_scale: 1.0,
+        decay: 1.0,
+        seed: [1, 2, 3, 4],
+    };
+
+    let mut training = teacher.new_training(&model);
+    let mut noise = Vec::new();
+    for _ in 0..2000 {
+        noise.push(training.noise_std_dev());
+        training.teach_batch(&cost, &mut model, &history);
+    }
+    println!("{:?}", model);
+
+    assert!(noise.windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(noise[noise.len() - 1] < 0.02);
+    assert!((model.m - 2.0).abs() < 0.1);
+    assert!((model.c - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn integer_truths() {
+
+    use std::iter::repeat;
+    use vikos::{learn_history, into_float_history};
+
+    let history = [1, 3, 4, 7, 8, 11, 29];
+
+    let mut model = model::Constant::new(0.0);
+    let teacher = teacher::GradientDescentAl { l0: 0.3, t: 4.0 };
+    learn_history(&teacher,
+                  &cost::LeastSquares {},
+                  &mut model,
+                  into_float_history(repeat(()).zip(history.iter().cycle().take(100).cloned())));
+
+    assert!((model.c - 9.0).abs() < 0.1);
+}
+
+#[test]
+fn inert_sgd_config_matches_momentum() {
+
+    use vikos::learn_history;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let start = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let cost = cost::LeastSquares {};
+
+    let config = teacher::InertSgdConfig {
+        learning_rate: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+    let model = config.run(&cost, start.clone(), history.iter().cycle().take(1500).cloned());
+
+    let teacher = teacher::Momentum {
+        l0: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+    let mut expected = start;
+    learn_history(&teacher,
+                  &cost,
+                  &mut expected,
+                  history.iter().cycle().take(1500).cloned());
+
+    assert_eq!(expected.m, model.m);
+    assert_eq!(expected.c, model.c);
+    assert!((model.m[0] - 1.0).abs() < 0.1);
+    assert!((model.m[1] - 2.0).abs() < 0.1);
+}
+
+#[test]
+fn platt_scaling_overconfident() {
+
+    use vikos::Model;
+
+    // Positive class with probability `logistic(x)`, as frequency of ten events per `x`
+    let mut history = Vec::new();
+    for i in -10..11 {
+        let x = i as f64 / 5.0;
+        let p = 1.0 / (1.0 + (-x).exp());
+        let positives = (p * 10.0).round() as usize;
+        for j in 0..10 {
+            history.push((x, if j < positives { 1.0 } else { 0.0 }));
+        }
+    }
+
+    // Predicting `logistic(4x)` is overconfident
+    let mut overconfident = model::Logistic::with_base_rate(0.0, 0.5);
+    *overconfident.coefficent(0) = -4.0;
+
+    let calibration_error = |predict: &Fn(f64) -> f64| {
+        (-10..11)
+            .map(|i| i as f64 / 5.0)
+            .map(|x| (predict(x) - 1.0 / (1.0 + (-x).exp())).powi(2))
+            .sum::<f64>()
+    };
+
+    let mut scaling = model::PlattScaling::new(overconfident.clone());
+    let teacher = teacher::GradientDescent { learning_rate: 0.1 };
+    scaling.calibrate(&teacher, history.iter().cycle().take(42000).cloned());
+
+    let before = calibration_error(&|x| overconfident.predict(&x));
+    let after = calibration_error(&|x| scaling.calibrated_predict(&x));
+    println!("before: {}, after: {}", before, after);
+    assert!(after < before / 2.0);
+}
+
+#[test]
+fn learn_epochs_cancelled_after_first_epoch() {
+
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use vikos::{Cost, Model, StopReason, learn_epochs_cancellable};
+
+    // Sets `cancel` once the cost of the whole history has been evaluated twice, i.e. before
+    // and after the first epoch
+    struct CancelAfterFirstEpoch<'a> {
+        cancel: &'a AtomicBool,
+        calls: Cell<usize>,
+        events: usize,
+    }
+
+    impl<'a> Cost<f64> for CancelAfterFirstEpoch<'a> {
+        fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+            cost::LeastSquares {}.outer_derivative(prediction, truth)
+        }
+
+        fn cost(&self, prediction: f64, truth: f64) -> f64 {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() == 2 * self.events {
+                self.cancel.store(true, Ordering::SeqCst);
+            }
+            cost::LeastSquares {}.cost(prediction, truth)
+        }
+    }
+
+    let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0)];
+    let cancel = AtomicBool::new(false);
+    let cost = CancelAfterFirstEpoch {
+        cancel: &cancel,
+        calls: Cell::new(0),
+        events: history.len(),
+    };
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    let teacher = teacher::GradientDescent { learning_rate: 0.1 };
+
+    let report = learn_epochs_cancellable(&teacher,
+                                          &cost,
+                                          &mut model,
+                                          &history,
+                                          1000,
+                                          1e-9,
+                                          Some(&cancel));
+
+    assert_eq!(StopReason::Cancelled, report.stopped_reason);
+    assert_eq!(1, report.epochs_run);
+    assert!(!report.converged);
+    assert!(model.predict(&1.0).is_finite());
+    assert!(model.c > 0.0);
+}
+
+#[test]
+fn scaled_least_squares_large_magnitudes() {
+
+    use vikos::learn_history;
+
+    // `2 * error * feature` overflows for plain least squares
+    let history = [(1e155, 2e155), (2e155, 4e155), (3e155, 6e155)];
+    let teacher = teacher::GradientDescent { learning_rate: 0.05 };
+
+    let mut overflowing = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher,
+                  &cost::LeastSquares {},
+                  &mut overflowing,
+                  history.iter().cycle().take(30).cloned());
+    assert!(!overflowing.m.is_finite());
+
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher,
+                  &cost::ScaledLeastSquares { scale: 1e155 },
+                  &mut model,
+                  history.iter().cycle().take(300).cloned());
+    assert!(model.m.is_finite() && model.c.is_finite());
+    assert!((model.m - 2.0).abs() < 0.01);
+}
+
+#[test]
+fn caching_model_invalidated_by_coefficent_change() {
+
+    use vikos::Model;
+
+    // Counts of two kinds of items as input
+    let inner = model::ClosureModel::new(vec![1.0, 2.0],
+                                         |w: &[f64], &(a, b): &(u32, u32)| {
+                                             w[0] * a as f64 + w[1] * b as f64
+                                         },
+                                         |_: &[f64], ci, &(a, b): &(u32, u32)| {
+                                             [a as f64, b as f64][ci]
+                                         });
+    let mut model = model::CachingModel::new(inner);
+
+    assert_eq!(5.0, model.predict(&(1, 2)));
+    assert_eq!(5.0, model.predict(&(1, 2)));
+    assert_eq!(1, model.cached());
+
+    *model.coefficent(1) = 3.0;
+    assert_eq!(0, model.cached());
+    assert_eq!(7.0, model.predict(&(1, 2)));
+    assert_eq!(model.model().predict(&(4, 1)), model.predict(&(4, 1)));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn multi_start_escapes_local_minimum() {
+
+    use rand::Rng;
+    use std::iter::repeat;
+    use vikos::{learn_history, mean_cost, Model};
+    use vikos::model_selection::multi_start;
+
+    // The squared prediction has its global minimum of zero close to `w = -1` and a local
+    // minimum of about `0.36` close to `w = 1`
+    let predict = |w: &[f64], _: &()| (w[0] * w[0] - 1.0).powi(2) + 0.3 * (w[0] + 1.0);
+    let gradient = |w: &[f64], _, _: &()| 4.0 * w[0] * (w[0] * w[0] - 1.0) + 0.3;
+    let history = [((), 0.0)];
+    let cost = cost::LeastSquares {};
+
+    let train = |model: &mut model::ClosureModel<(), _, _>| {
+        learn_history(&teacher::GradientDescent { learning_rate: 0.01 },
+                      &cost,
+                      model,
+                      repeat(((), 0.0)).take(5000));
+        mean_cost(&cost, model, &history)
+    };
+    let init = |rng: &mut rand::XorShiftRng| {
+        model::ClosureModel::new(vec![rng.gen_range(-1.5, 1.5)], predict, gradient)
+    };
+
+    let seeds = [[0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb],
+                 [0x2545f491, 0x9e3779b9, 0x7f4a7c15, 0x6a09e667],
+                 [0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f],
+                 [0x9b05688c, 0x1f83d9ab, 0x5be0cd19, 0xcbbb9d5d],
+                 [0x629a292a, 0x9159015a, 0x152fecd8, 0x67332667]];
+    let single_start_failures = seeds.iter()
+        .map(|&seed| multi_start(&train, &init, 1, seed))
+        .filter(|model| model.predict(&()).abs() > 0.1)
+        .count();
+    assert!(single_start_failures > 0);
+
+    for &seed in &seeds {
+        let model = multi_start(&train, &init, 10, seed);
+        println!("{:?}", model.coefficents);
+        assert!(model.predict(&()).abs() < 0.1);
+    }
+}
+
+#[test]
+fn momentum_reset_on_plateau() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [(0.0, 3.0), (1.0, 4.0), (2.0, 5.0)];
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    // The high inertia makes the coefficents overshoot and oscillate around the optimum
+    let teacher = teacher::MomentumReset {
+        momentum: teacher::Momentum {
+            l0: 0.05,
+            t: 100000.0,
+            inertia: 0.995,
+        },
+        patience: 3,
+    };
+
+    let mut training = teacher.new_training(&model);
+    let mut first_reset = None;
+    for _ in 0..200 {
+        training.teach_batch(&cost, &mut model, &history);
+        if training.resets > 0 && first_reset.is_none() {
+            first_reset = Some(training.last_epoch_cost);
+        }
+    }
+
+    let cost_at_reset = first_reset.expect("Velocity has never been reset");
+    assert!(training.last_epoch_cost < cost_at_reset);
+    assert!(training.last_epoch_cost < 0.01);
+
+    // Without resets the coefficents still oscillate
+    let mut plain = model::Linear { m: 0.0, c: 0.0 };
+    let mut training = teacher.momentum.new_training(&plain);
+    for _ in 0..200 {
+        training.teach_batch(&cost, &mut plain, &history);
+    }
+    assert!(vikos::mean_cost(&cost, &plain, &history) > 0.01);
+}
+
+#[test]
+fn linear_fixed_dimensions() {
+
+    use vikos::{learn_history, Model};
+
+    let one = model::Linear { m: [2.0], c: 1.0 };
+    assert_eq!(2, one.num_coefficents());
+    assert_eq!(5.0, one.predict(&[2.0]));
+    assert_eq!(2.0, one.gradient(0, &[2.0]));
+    assert_eq!(1.0, one.gradient(1, &[2.0]));
+
+    let two = model::Linear {
+        m: [2.0, -1.0],
+        c: 1.0,
+    };
+    assert_eq!(3, two.num_coefficents());
+    assert_eq!(4.0, two.predict(&[2.0, 1.0]));
+    assert_eq!(1.0, two.gradient(1, &[2.0, 1.0]));
+    assert_eq!(1.0, two.gradient(2, &[2.0, 1.0]));
+
+    // y = x0 - 2 * x1 + 3 * x2 + 4
+    let history = [([0.0, 0.0, 0.0], 4.0),
+                   ([1.0, 0.0, 0.0], 5.0),
+                   ([0.0, 1.0, 0.0], 2.0),
+                   ([0.0, 0.0, 1.0], 7.0),
+                   ([1.0, 1.0, 1.0], 6.0)];
+    let mut three = model::Linear {
+        m: [0.0; 3],
+        c: 0.0,
+    };
+    assert_eq!(4, three.num_coefficents());
+    learn_history(&teacher::GradientDescent { learning_rate: 0.1 },
+                  &cost::LeastSquares {},
+                  &mut three,
+                  history.iter().cycle().take(5000).cloned());
+    assert!((three.m[0] - 1.0).abs() < 0.01);
+    assert!((three.m[1] + 2.0).abs() < 0.01);
+    assert!((three.m[2] - 3.0).abs() < 0.01);
+    assert!((three.c - 4.0).abs() < 0.01);
+}
+
+#[test]
+fn standardizing_pipeline() {
+
+    use vikos::preprocess::{Pipeline, Standardizer};
+
+    // y = 0.01 * x0 + 100 * x1 + 1 with features of very different scale
+    let data: Vec<_> = (0..20)
+        .map(|i| {
+            let x = vec![100.0 * i as f64, 0.01 * (i % 5) as f64];
+            let y = 0.01 * x[0] + 100.0 * x[1] + 1.0;
+            (x, y)
+        })
+        .collect();
+
+    let mut pipeline: Pipeline<Standardizer, _, _> =
+        Pipeline::new(model::Linear {
+                          m: vec![0.0; 2],
+                          c: 0.0,
+                      },
+                      teacher::GradientDescent { learning_rate: 0.05 },
+                      500);
+    pipeline.fit(&cost::LeastSquares {}, &data);
+
+    // Raw inputs are standardized automatically
+    for &(ref input, truth) in &data {
+        assert!((pipeline.predict(input) - truth).abs() < 0.01);
+    }
+    assert!((pipeline.predict(&vec![1000.0, 0.02]) - 13.0).abs() < 0.01);
+}
+
+#[test]
+fn learn_epochs_throughput() {
+
+    use vikos::learn_epochs;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let report = learn_epochs(&teacher::GradientDescent { learning_rate: 0.01 },
+                              &cost::LeastSquares {},
+                              &mut model,
+                              &history,
+                              1000,
+                              0.0);
+
+    assert_eq!(3000, report.events_learned);
+    let throughput = report.events_per_second();
+    assert!(throughput.is_finite());
+    assert!(throughput > 0.0);
+}
+
+#[test]
+fn lbfgs_converges_in_few_steps() {
+
+    use vikos::learn_epochs;
+
+    let history = [([0.0, 0.0], 3.0), ([1.0, 0.0], 4.0), ([0.0, 1.0], 5.0), ([1.0, 1.0], 6.0)];
+    let cost = cost::LeastSquares {};
+
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let report = learn_epochs(&teacher::Lbfgs { memory: 5 },
+                              &cost,
+                              &mut model,
+                              &history,
+                              100,
+                              1e-12);
+    assert!(report.converged);
+    assert!(report.epochs_run < 20);
+    assert!((model.m[0] - 1.0).abs() < 1e-4);
+    assert!((model.m[1] - 2.0).abs() < 1e-4);
+    assert!((model.c - 3.0).abs() < 1e-4);
+
+    let mut sgd = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let sgd_report = learn_epochs(&teacher::GradientDescent { learning_rate: 0.01 },
+                                  &cost,
+                                  &mut sgd,
+                                  &history,
+                                  100,
+                                  1e-12);
+    assert!(sgd_report.final_cost > 100.0 * report.final_cost);
+}
+
+#[test]
+fn lbfgs_without_memory() {
+
+    use vikos::learn_epochs;
+
+    let history = [([0.0, 0.0], 3.0), ([1.0, 0.0], 4.0), ([0.0, 1.0], 5.0), ([1.0, 1.0], 6.0)];
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let report = learn_epochs(&teacher::Lbfgs { memory: 0 },
+                              &cost::LeastSquares {},
+                              &mut model,
+                              &history,
+                              10,
+                              1e-12);
+    // The mean cost of the initial model is 21.5
+    assert!(report.final_cost < 1.0);
+}
+
+#[test]
+fn lbfgs_keeps_model_if_line_search_fails() {
+
+    use vikos::{Cost, Teacher, Training};
+
+    // Gradient pointing uphill, so no step along it decreases the cost
+    struct Uphill;
+
+    impl Cost<f64> for Uphill {
+        fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+            -cost::LeastSquares {}.outer_derivative(prediction, truth)
+        }
+
+        fn cost(&self, prediction: f64, truth: f64) -> f64 {
+            cost::LeastSquares {}.cost(prediction, truth)
+        }
+    }
+
+    let mut model = model::Linear { m: 0.5, c: 0.5 };
+    let mut training = teacher::Lbfgs { memory: 5 }.new_training(&model);
+    training.teach_batch(&Uphill, &mut model, &[(0.0, 1.0), (1.0, 2.0)]);
+    assert_eq!(model::Linear { m: 0.5, c: 0.5 }, model);
+}
+
+#[test]
+fn predict_rows_matches_predict() {
+
+    use vikos::Model;
+
+    let rows = [[0.0, 0.0], [1.0, 2.0], [-3.0, 0.5], [2.5, -1.0]];
+
+    let linear = model::Linear {
+        m: [0.5, -2.0],
+        c: 1.0,
+    };
+    let predictions = linear.predict_rows(&rows);
+    assert_eq!(rows.len(), predictions.len());
+    for (row, &prediction) in rows.iter().zip(&predictions) {
+        assert_eq!(linear.predict(row), prediction);
+    }
+
+    let mut logistic = model::Logistic::default();
+    *logistic.coefficent(0) = 0.5;
+    *logistic.coefficent(1) = -2.0;
+    *logistic.coefficent(2) = 1.0;
+    let predictions = logistic.predict_rows(&rows);
+    assert_eq!(rows.len(), predictions.len());
+    for (row, &prediction) in rows.iter().zip(&predictions) {
+        assert_eq!(logistic.predict(row), prediction);
+    }
+}
+
+#[test]
+fn cyclical_learning_rate_is_triangular() {
+
+    use vikos::{Teacher, Training};
+
+    let teacher = teacher::CyclicalLr {
+        base: 0.1,
+        max: 0.5,
+        step_size: 4.0,
+    };
+    let mut model = model::Constant::new(0.0);
+    let mut training = teacher.new_training(&model);
+    let cost = cost::LeastSquares {};
+
+    let mut rates = Vec::new();
+    for _ in 0..17 {
+        rates.push(training.learning_rate());
+        training.teach_event(&cost, &mut model, &(), 1.0);
+    }
+
+    let expected = [0.1, 0.2, 0.3, 0.4, 0.5, 0.4, 0.3, 0.2];
+    for (i, rate) in rates.iter().enumerate() {
+        // Period of the wave is 2 * step_size
+        assert!((rate - expected[i % 8]).abs() < 1e-12,
+                "event {}: expected {}, got {}",
+                i,
+                expected[i % 8],
+                rate);
+    }
+    assert!(rates.iter().all(|&rate| rate >= 0.1 - 1e-12 && rate <= 0.5 + 1e-12));
+}
+
+#[test]
+fn model_equality() {
+
+    use vikos::testing::ApproxEq;
+
+    let model = model::Linear {
+        m: [1.0, 2.0],
+        c: 3.0,
+    };
+    let clone = model.clone();
+    assert_eq!(model, clone);
+
+    let mut perturbed = model.clone();
+    perturbed.m[1] += 1e-6;
+    assert!(model != perturbed);
+    assert!(model.approx_eq(&perturbed, 1e-3));
+    assert!(!model.approx_eq(&perturbed, 1e-9));
+
+    assert_eq!(model::Constant::<()>::new(2.0), model::Constant::new(2.0));
+}
+
+#[test]
+fn normalized_gradient_descent_unscaled_features() {
+
+    use vikos::{learn_epochs, Model};
+
+    // Same events as `logistic_sgd_2d_least_squares`, but the features are scaled by a thousand
+    let history = [([2700.0, 2500.0], 0.0),
+                   ([1400.0, 2300.0], 0.0),
+                   ([3300.0, 4400.0], 0.0),
+                   ([1300.0, 1800.0], 0.0),
+                   ([3000.0, 3000.0], 0.0),
+                   ([7600.0, 2700.0], 1.0),
+                   ([5300.0, 2000.0], 1.0),
+                   ([6900.0, 1700.0], 1.0),
+                   ([8600.0, -200.0], 1.0),
+                   ([7600.0, 3500.0], 1.0)];
+    let cost = cost::LeastSquares {};
+
+    let mut model = model::Logistic::default();
+    learn_epochs(&teacher::NormalizedGradientDescent { learning_rate: 0.0001 },
+                 &cost,
+                 &mut model,
+                 &history,
+                 100,
+                 0.0);
+    println!("{:?}", model);
+
+    let classification_errors = history.iter()
+        .filter(|&&(input, truth)| model.predict(&input).round() != truth)
+        .count();
+    assert_eq!(0, classification_errors);
+}
+
+#[test]
+fn grow_linear_model() {
+
+    use vikos::Model;
+    use vikos::model::GrowableModel;
+
+    let mut model = model::Linear {
+        m: vec![2.0],
+        c: 1.0,
+    };
+    assert_eq!(2, model.num_coefficents());
+    assert_eq!(5.0, model.predict(&vec![2.0]));
+
+    model.push_coefficent(3.0);
+    model.push_coefficent(-1.0);
+    assert_eq!(4, model.num_coefficents());
+    assert_eq!(vec![2.0, 3.0, -1.0], model.m);
+    assert_eq!(1.0, model.c);
+    assert_eq!(2.0 * 2.0 + 3.0 * 1.0 - 1.0 * 4.0 + 1.0,
+               model.predict(&vec![2.0, 1.0, 4.0]));
+
+    model.truncate(2);
+    assert_eq!(2, model.num_coefficents());
+    assert_eq!(5.0, model.predict(&vec![2.0]));
+
+    // The offset is never removed
+    model.truncate(0);
+    assert_eq!(1, model.num_coefficents());
+    assert_eq!(1.0, model.predict(&vec![]));
+}
+
+#[test]
+fn decision_stump_to_dot() {
+
+    let stump: model::DecisionStump<[f64; 2]> = model::DecisionStump::new(1, 2.5, -1.25, 4.0);
+    let dot = stump.to_dot();
+    println!("{}", dot);
+
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("x1 < 2.5"));
+    assert!(dot.contains("\"-1.25\""));
+    assert!(dot.contains("\"4\""));
+    assert!(dot.contains("split -> left"));
+    assert!(dot.contains("split -> right"));
+}
+
+#[test]
+fn softmax_three_classes() {
+
+    let history = [([0.0, 0.0], 0),
+                   ([0.5, 0.3], 0),
+                   ([-0.4, 0.2], 0),
+                   ([4.0, 0.0], 1),
+                   ([4.5, 0.5], 1),
+                   ([3.8, -0.4], 1),
+                   ([0.0, 4.0], 2),
+                   ([0.4, 4.6], 2),
+                   ([-0.3, 3.7], 2)];
+
+    let cost = cost::CategoricalCrossEntropy {};
+    let mut model = model::Softmax::new([0.0, 0.0], 3);
+
+    let initial_cost: f64 = history.iter()
+        .map(|&(ref input, class)| cost.cost(&model.predict_proba(input), class))
+        .sum();
+    for &(ref input, class) in history.iter().cycle().take(900) {
+        model.teach_event(&cost, input, class, 0.1);
+    }
+    let final_cost: f64 = history.iter()
+        .map(|&(ref input, class)| cost.cost(&model.predict_proba(input), class))
+        .sum();
+    println!("{:?}", model);
+
+    assert!(final_cost < initial_cost);
+    for &(ref input, class) in &history {
+        assert_eq!(class, model.predict_class(input));
+        let probabilities = model.predict_proba(input);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(probabilities[class] > 0.5);
+    }
+}
+
+#[test]
+fn batch_gradient_reduction() {
+
+    use vikos::{Teacher, Training};
+    use vikos::training::Reduction;
+
+    let batch = [([1.0, 2.0], 3.0), ([0.0, 1.0], -1.0), ([2.0, -1.0], 0.5), ([1.5, 0.5], 2.0)];
+    let cost = cost::LeastSquares {};
+    let start = model::Linear {
+        m: [0.5, -0.5],
+        c: 0.25,
+    };
+
+    let step = |reduction| {
+        let teacher = teacher::BatchGradientDescent {
+            learning_rate: 0.01,
+            reduction: reduction,
+        };
+        let mut model = start.clone();
+        teacher.new_training(&model).teach_batch(&cost, &mut model, &batch);
+        [model.m[0] - start.m[0], model.m[1] - start.m[1], model.c - start.c]
+    };
+
+    assert_eq!(Reduction::Mean, Reduction::default());
+    let sum = step(Reduction::Sum);
+    let mean = step(Reduction::Mean);
+    for (s, m) in sum.iter().zip(&mean) {
+        assert!(m.abs() > 0.0);
+        assert!((s - batch.len() as f64 * m).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn generalized_linear_models() {
+
+    use vikos::Model;
+    use vikos::model::{GeneralizedLinearModel, Linear};
+
+    let input = [0.5, -2.0];
+
+    let linear = Linear {
+        m: [1.5, 0.25],
+        c: -1.0,
+    };
+    assert_eq!(1.5 * 0.5 - 0.25 * 2.0 - 1.0, linear.predict(&input));
+    assert_eq!(3, linear.num_coefficents());
+    assert_eq!(vec![0.5, -2.0, 1.0],
+               (0..3).map(|ci| linear.gradient(ci, &input)).collect::<Vec<_>>());
+
+    let mut logistic = model::Logistic::default();
+    *logistic.coefficent(0) = 1.5;
+    *logistic.coefficent(1) = 0.25;
+    *logistic.coefficent(2) = -1.0;
+    let z = linear.predict(&input);
+    let p = 1.0 / (1.0 + z.exp());
+    assert_eq!(p, logistic.predict(&input));
+    for ci in 0..3 {
+        assert_eq!(-p * (1.0 - p) * linear.gradient(ci, &input),
+                   logistic.gradient(ci, &input));
+    }
+
+    // A new generalized linear model only needs its activation
+    #[derive(Clone)]
+    struct Exponential(Linear<[f64; 2]>);
+
+    impl GeneralizedLinearModel for Exponential {
+        type Features = [f64; 2];
+        fn linear(&self) -> &Linear<[f64; 2]> {
+            &self.0
+        }
+        fn linear_mut(&mut self) -> &mut Linear<[f64; 2]> {
+            &mut self.0
+        }
+        fn activation(&self, linear_combination: f64) -> f64 {
+            linear_combination.exp()
+        }
+        fn activation_derivative(&self, linear_combination: f64) -> f64 {
+            linear_combination.exp()
+        }
+    }
+
+    let mut exponential = Exponential(linear.clone());
+    assert_eq!(z.exp(), exponential.predict(&input));
+    assert_eq!(z.exp() * 0.5, exponential.gradient(0, &input));
+    *exponential.coefficent(2) = 0.0;
+    assert_eq!(0.0, exponential.0.c);
+}
+
+#[test]
+fn clip_gradient_norm() {
+
+    use vikos::{Teacher, Training};
+
+    let teacher = teacher::ClipGradientNorm {
+        teacher: teacher::GradientDescent { learning_rate: 1.0 },
+        max_norm: 2.0,
+    };
+    let cost = cost::LeastSquares {};
+    let start = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let step = |model: &model::Linear<[f64; 2]>| {
+        ((model.m[0] - start.m[0]).powi(2) + (model.m[1] - start.m[1]).powi(2) +
+         (model.c - start.c).powi(2))
+            .sqrt()
+    };
+
+    // Huge gradient, rescaled to a step of exactly `max_norm`
+    let mut model = start.clone();
+    let mut training = teacher.new_training(&model);
+    training.teach_event(&cost, &mut model, &[100.0, -50.0], 1000.0);
+    assert!((step(&model) - 2.0).abs() < 1e-12);
+    assert_eq!(1, training.clipped);
+    // Direction is kept
+    assert!((model.m[0] / model.m[1] + 2.0).abs() < 1e-12);
+
+    // Small gradient, taught unchanged
+    let mut clipped = start.clone();
+    let mut plain = start.clone();
+    training.teach_event(&cost, &mut clipped, &[0.1, 0.2], 0.3);
+    teacher::GradientDescent { learning_rate: 1.0 }
+        .new_training(&plain)
+        .teach_event(&cost, &mut plain, &[0.1, 0.2], 0.3);
+    assert!(step(&clipped) < 2.0);
+    assert_eq!(plain, clipped);
+    assert_eq!(1, training.clipped);
+}
+
+#[test]
+fn input_dependent_cost() {
+
+    use vikos::{Cost, Model, Teacher, Training};
+
+    // Least squares, with the gradient of each event weighted by its first feature
+    struct FeatureWeighted;
+
+    impl Cost<f64> for FeatureWeighted {
+        fn outer_derivative(&self, prediction: f64, truth: f64) -> f64 {
+            cost::LeastSquares {}.outer_derivative(prediction, truth)
+        }
+        fn cost(&self, prediction: f64, truth: f64) -> f64 {
+            cost::LeastSquares {}.cost(prediction, truth)
+        }
+        fn gradient_full<M>(&self,
+                            prediction: f64,
+                            truth: f64,
+                            model: &M,
+                            features: &M::Input,
+                            coefficent: usize)
+                            -> f64
+            where M: Model
+        {
+            // For `Linear` the derivative by the first weight is the first feature
+            let weight = model.gradient(0, features);
+            weight * self.gradient(prediction, truth, model.gradient(coefficent, features))
+        }
+    }
+
+    let start = model::Linear {
+        m: [0.0, 1.0],
+        c: 0.0,
+    };
+    let teacher = teacher::GradientDescent { learning_rate: 0.1 };
+    let teach = |cost: &FeatureWeighted, input: [f64; 2]| {
+        let mut model = start.clone();
+        teacher.new_training(&model).teach_event(cost, &mut model, &input, 3.0);
+        model
+    };
+    let plain = |input: [f64; 2]| {
+        let mut model = start.clone();
+        teacher.new_training(&model).teach_event(&cost::LeastSquares {}, &mut model, &input, 3.0);
+        model
+    };
+
+    // Events with a weight of zero are ignored
+    assert_eq!(start, teach(&FeatureWeighted, [0.0, 1.0]));
+    // A weight of one behaves like least squares
+    assert_eq!(plain([1.0, 1.0]), teach(&FeatureWeighted, [1.0, 1.0]));
+    // A weight of two doubles the step
+    let weighted = teach(&FeatureWeighted, [2.0, 1.0]);
+    let unweighted = plain([2.0, 1.0]);
+    assert!((weighted.c - 2.0 * unweighted.c).abs() < 1e-12);
+    assert!((weighted.m[1] - 1.0 - 2.0 * (unweighted.m[1] - 1.0)).abs() < 1e-12);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn training_rng_reproducible() {
+
+    use rand::Rng;
+    use vikos::learn_history;
+    use vikos::random::TrainingRng;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0), ([3.0, 1.0], 8.0)];
+    let cost = cost::LeastSquares {};
+
+    let run = |seed: [u32; 4]| {
+        let mut rng = TrainingRng::new(seed);
+        // Initialization, shuffling, then seeding the teacher
+        let mut model = model::Linear {
+            m: [rng.next_f64(), rng.next_f64()],
+            c: rng.next_f64(),
+        };
+        let mut events = history.to_vec();
+        rng.shuffle(&mut events);
+        let teacher = teacher::GradientDescentDropout {
+            learning_rate: 0.01,
+            dropout: 0.3,
+            seed: rng.next_seed(),
+        };
+        learn_history(&teacher, &cost, &mut model, events.iter().cycle().take(100).cloned());
+        model
+    };
+
+    let seed = [0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb];
+    assert_eq!(run(seed), run(seed));
+    assert!(run(seed) != run([0x4f2bd7a1, 0x5e9c4d7f, 0x6b0e5a3c, 0x7a1d8e2b]));
+}
+
+#[test]
+fn teach_batched_stream_converges() {
+
+    use vikos::{teach_batched_stream, mean_cost};
+
+    // y = 2 * x0 - x1 + 1, stored in pages of four events, which are borrowed lazily
+    let pages: Vec<Vec<([f64; 2], f64)>> = (0..5)
+        .map(|page| {
+            (0..4)
+                .map(|i| {
+                    let x = [(page * 4 + i) as f64 / 10.0, ((page + i) % 3) as f64];
+                    (x, 2.0 * x[0] - x[1] + 1.0)
+                })
+                .collect()
+        })
+        .collect();
+    let batches = (0..400).map(|i| &pages[i % pages.len()][..]);
+
+    let cost = cost::LeastSquares {};
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    teach_batched_stream(&teacher::GradientDescent { learning_rate: 0.05 },
+                         &cost,
+                         &mut model,
+                         batches);
+    println!("{:?}", model);
+
+    let history: Vec<_> = pages.iter().flat_map(|page| page.iter().cloned()).collect();
+    assert!(mean_cost(&cost, &model, &history) < 1e-6);
+    assert!((model.m[0] - 2.0).abs() < 1e-2);
+    assert!((model.m[1] + 1.0).abs() < 1e-2);
+    assert!((model.c - 1.0).abs() < 1e-2);
+}
+
+#[test]
+fn model_from_coefficents() {
+
+    use vikos::{coefficents, Error, Model};
+    use vikos::model::FromCoefficents;
+
+    let model = model::Linear {
+        m: [1.5, -2.0],
+        c: 0.5,
+    };
+    let flat = coefficents(&model);
+    assert_eq!(vec![1.5, -2.0, 0.5], flat);
+
+    let restored = model::Linear::<[f64; 2]>::from_coefficents(&flat).unwrap();
+    assert_eq!(model, restored);
+    for input in &[[0.0, 0.0], [1.0, 2.0], [-3.0, 0.25]] {
+        assert_eq!(model.predict(input), restored.predict(input));
+    }
+
+    let restored = model::Linear::<Vec<f64>>::from_coefficents(&flat).unwrap();
+    assert_eq!(vec![1.5, -2.0], restored.m);
+    assert_eq!(0.5, restored.c);
+
+    let logistic = model::Logistic::<[f64; 2]>::from_coefficents(&flat).unwrap();
+    assert_eq!(flat, coefficents(&logistic));
+
+    assert_eq!(Err(Error::CoefficentCountMismatch {
+                   expected: 3,
+                   actual: 2,
+               }),
+               model::Linear::<[f64; 2]>::from_coefficents(&[1.0, 2.0]));
+}
+
+#[test]
+fn ema_lags_and_settles() {
+
+    use vikos::{Teacher, Training};
+
+    let teacher = teacher::Ema {
+        teacher: teacher::GradientDescent { learning_rate: 0.05 },
+        decay: 0.9,
+    };
+    let cost = cost::LeastSquares {};
+    let mut model = model::Constant::new(0.0);
+    let mut training = teacher.new_training(&model);
+
+    for _ in 0..20 {
+        training.teach_event(&cost, &mut model, &(), 5.0);
+        // Raw coefficent approaches the truth from below, the average lags behind
+        assert!(training.ema_model().c < model.c);
+    }
+
+    for _ in 0..500 {
+        training.teach_event(&cost, &mut model, &(), 5.0);
+    }
+    assert!((model.c - 5.0).abs() < 1e-6);
+    assert!((training.ema_model().c - model.c).abs() < 1e-6);
+}
+
+#[test]
+fn robust_scaled_down_weights_noisy_region() {
+
+    use vikos::learn_history;
+
+    // Low noise region follows y = 2x + 1, the high noise region scatters around a different
+    // trend.
+    let mut history = Vec::new();
+    for i in 0..50 {
+        let x = i as f64 / 10.0;
+        let noise = if i % 2 == 0 { 0.01 } else { -0.01 };
+        history.push((x, 2.0 * x + 1.0 + noise));
+    }
+    for i in 0..50 {
+        let x = 5.0 + i as f64 / 10.0;
+        let noise = if i % 2 == 0 { 100.0 } else { -100.0 };
+        history.push((x, 10.0 - x + noise));
+    }
+
+    let cost = cost::LeastSquares {};
+    let teacher = teacher::RobustScaled {
+        teacher: teacher::GradientDescent { learning_rate: 0.0003 },
+        window: 5,
+        noise_floor: 0.1,
+    };
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher,
+                  &cost,
+                  &mut model,
+                  history.iter().cycle().take(100 * history.len()).cloned());
+
+    let mut unscaled = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher::GradientDescent { learning_rate: 0.0003 },
+                  &cost,
+                  &mut unscaled,
+                  history.iter().cycle().take(100 * history.len()).cloned());
+
+    assert!((model.m - 2.0).abs() < 0.3);
+    assert!((unscaled.m - 2.0).abs() > 2.0);
+}
+
+#[test]
+#[should_panic(expected = "window must contain at least one event")]
+fn robust_scaled_without_window() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::RobustScaled {
+        teacher: teacher::GradientDescent { learning_rate: 0.1 },
+        window: 0,
+        noise_floor: 0.1,
+    };
+    teacher.new_training(&model::Constant::<()>::new(0.0));
+}
+
+#[test]
+fn named_features_in_equation() {
+
+    use vikos::Model;
+    use vikos::model::Describe;
+
+    let model = model::Linear {
+            m: [1.5, -2.0],
+            c: 0.25,
+        }
+        .with_feature_names(vec!["age".to_string(), "income".to_string()]);
+    assert_eq!("y = 1.50*age - 2.00*income + 0.25", model.to_equation());
+    assert_eq!(1.5 * 2.0 - 2.0 * 1.0 + 0.25, model.predict(&[2.0, 1.0]));
+
+    // Features without a name keep the default
+    let model = model::Linear {
+            m: [1.0, 2.0],
+            c: 3.0,
+        }
+        .with_feature_names(vec!["age".to_string()]);
+    assert_eq!("y = 1.00*age + 2.00*x1 + 3.00", model.to_equation());
+}
+
+#[test]
+fn rprop_without_learning_rate() {
+
+    use vikos::{Teacher, Training};
+
+    let history = [([0.0, 0.0], 3.0), ([1.0, 0.0], 4.0), ([0.0, 1.0], 5.0), ([1.0, 1.0], 6.0)];
+    let cost = cost::LeastSquares {};
+
+    let mut model = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let mut training = teacher::Rprop {}.new_training(&model);
+    for _ in 0..200 {
+        training.teach_batch(&cost, &mut model, &history);
+    }
+    assert!((model.m[0] - 1.0).abs() < 1e-4);
+    assert!((model.m[1] - 2.0).abs() < 1e-4);
+    assert!((model.c - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn constant_weighted_quantile() {
+
+    // Total weight 8, the cumulative weight passes 4 at truth 4
+    let history = [((), 1.0, 1.0), ((), 2.0, 1.0), ((), 3.0, 1.0), ((), 4.0, 5.0)];
+    let model = model::Constant::<()>::fit_weighted_quantile(history.iter().cloned(), 0.5);
+    assert_eq!(4.0, model.c);
+
+    // Zero weights are ignored, no matter how extreme the truth
+    let history = [((), 1.0, 1.0), ((), 2.0, 2.0), ((), 3.0, 1.0), ((), 100.0, 0.0)];
+    let model = model::Constant::<()>::fit_weighted_quantile(history.iter().cloned(), 0.5);
+    assert_eq!(2.0, model.c);
+
+    // Equal weights match the unweighted median, ties count with their summed weight
+    let history = [((), 1.0, 1.0), ((), 2.0, 1.0), ((), 2.0, 1.0), ((), 5.0, 1.0)];
+    let median = model::Constant::<()>::fit_median(history.iter().map(|&(x, t, _)| (x, t)));
+    let model = model::Constant::<()>::fit_weighted_quantile(history.iter().cloned(), 0.5);
+    assert_eq!(median.c, model.c);
+    let model = model::Constant::<()>::fit_weighted_quantile(history.iter().cloned(), 0.25);
+    assert_eq!(1.5, model.c);
+    let model = model::Constant::<()>::fit_weighted_quantile(history.iter().cloned(), 0.9);
+    assert_eq!(5.0, model.c);
+}
+
+#[test]
+fn sgdr_restarts_with_growing_periods() {
+
+    use vikos::{Teacher, Training};
+
+    let teacher = teacher::Sgdr {
+        t0: 4.0,
+        t_mult: 2.0,
+        lr_max: 0.5,
+        lr_min: 0.1,
+    };
+    let mut model = model::Constant::new(0.0);
+    let mut training = teacher.new_training(&model);
+    let cost = cost::LeastSquares {};
+
+    let mut rates = Vec::new();
+    for _ in 0..28 {
+        rates.push(training.learning_rate());
+        training.teach_event(&cost, &mut model, &(), 1.0);
+    }
+
+    // Periods of 4, 8 and 16 events start at events 0, 4 and 12
+    let restarts: Vec<usize> = (1..rates.len()).filter(|&i| rates[i] > rates[i - 1]).collect();
+    assert_eq!(vec![4, 12], restarts);
+    for &i in [0, 4, 12].iter() {
+        assert!((rates[i] - 0.5).abs() < 1e-12);
+    }
+    // Halfway through the second period the rate is halfway between the bounds
+    assert!((rates[8] - 0.3).abs() < 1e-12);
+    assert!(rates.iter().all(|&rate| rate > 0.1 && rate <= 0.5));
+}
+
+#[test]
+#[should_panic(expected = "Sgdr requires t0 > 0 and t_mult >= 1")]
+fn sgdr_with_shrinking_periods() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::Sgdr {
+        t0: 4.0,
+        t_mult: 0.5,
+        lr_max: 0.5,
+        lr_min: 0.1,
+    };
+    teacher.new_training(&model::Constant::<()>::new(0.0));
+}
+
+#[test]
+fn fit_linear_2d() {
+
+    use vikos::fit;
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let start = model::Linear {
+        m: [0.0, 0.0],
+        c: 0.0,
+    };
+    let teacher = teacher::Momentum {
+        l0: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+
+    let model = fit(&teacher, &cost::LeastSquares {}, &start, &history, 500);
+
+    assert!((model.m[0] - 1.0).abs() < 0.1);
+    assert!((model.m[1] - 2.0).abs() < 0.1);
+    assert!((model.c - 3.0).abs() < 0.1);
+    // The start model is left untouched
+    assert_eq!(0.0, start.c);
+}
+
+#[test]
+fn learning_rate_trace() {
+
+    use vikos::learn_epochs;
+
+    let history = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)];
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    let teacher = teacher::GradientDescentAl { l0: 0.1, t: 6.0 };
+    let report = learn_epochs(&teacher, &cost::LeastSquares {}, &mut model, &history, 10, -1.0);
+
+    assert_eq!(10, report.learning_rates.len());
+    for (epoch, rate) in report.learning_rates.iter().enumerate() {
+        // Each epoch teaches three events, so the schedule advances by three steps per epoch
+        let expected = 0.1 / (1.0 + (3 * epoch) as f64 / 6.0);
+        assert!((rate - expected).abs() < 1e-12);
+    }
+
+    // Batch trainings step once per epoch
+    let teacher = teacher::BatchGradientDescent {
+        learning_rate: 0.05,
+        reduction: training::Reduction::Mean,
+    };
+    let report = learn_epochs(&teacher, &cost::LeastSquares {}, &mut model, &history, 10, -1.0);
+    assert_eq!(vec![0.05; 10], report.learning_rates);
+
+    // Trainings without a learning rate leave the trace empty
+    let report = learn_epochs(&teacher::Rprop {},
+                              &cost::LeastSquares {},
+                              &mut model,
+                              &history,
+                              10,
+                              -1.0);
+    assert!(report.learning_rates.is_empty());
+}
+
+#[test]
+fn logistic_predict_class() {
+
+    use vikos::{learn_history, Model};
+
+    let history = [([2.7, 2.5], 0.0),
+                   ([1.4, 2.3], 0.0),
+                   ([3.3, 4.4], 0.0),
+                   ([1.3, 1.8], 0.0),
+                   ([3.0, 3.0], 0.0),
+                   ([7.6, 2.7], 1.0),
+                   ([5.3, 2.0], 1.0),
+                   ([6.9, 1.7], 1.0),
+                   ([8.6, -0.2], 1.0),
+                   ([7.6, 3.5], 1.0)];
+
+    let mut model = model::Logistic::default();
+    learn_history(&teacher::GradientDescent { learning_rate: 0.3 },
+                  &cost::MaxLikelihood {},
+                  &mut model,
+                  history.iter().cycle().take(20).cloned());
+
+    for &(input, _) in &history {
+        assert_eq!(model.predict(&input), model.predict_proba(&input));
+        assert_eq!(model.predict(&input).round() == 1.0,
+                   model.predict_class(&input, 0.5));
+    }
+    // A threshold of zero accepts every input
+    assert!(history.iter().all(|&(input, _)| model.predict_class(&input, 0.0)));
+}
+
+#[test]
+fn logistic_decision_boundary() {
+
+    use vikos::{learn_history, Model};
+    use vikos::model::decision_boundary;
+
+    let history = [([2.7, 2.5], 0.0),
+                   ([1.4, 2.3], 0.0),
+                   ([3.3, 4.4], 0.0),
+                   ([1.3, 1.8], 0.0),
+                   ([3.0, 3.0], 0.0),
+                   ([7.6, 2.7], 1.0),
+                   ([5.3, 2.0], 1.0),
+                   ([6.9, 1.7], 1.0),
+                   ([8.6, -0.2], 1.0),
+                   ([7.6, 3.5], 1.0)];
+
+    let mut model = model::Logistic::default();
+    learn_history(&teacher::GradientDescent { learning_rate: 0.3 },
+                  &cost::MaxLikelihood {},
+                  &mut model,
+                  history.iter().cycle().take(20).cloned());
+
+    let boundary = decision_boundary(&model, (0.0, 10.0), 11);
+    assert_eq!(11, boundary.len());
+    assert_eq!((0.0, 10.0), (boundary[0].0, boundary[10].0));
+    for &(x, y) in &boundary {
+        assert!((model.predict(&[x, y]) - 0.5).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn freeze_offset() {
+
+    use vikos::learn_history;
+
+    let history = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)];
+    let teacher = teacher::FreezeCoefficents {
+        teacher: teacher::GradientDescent { learning_rate: 0.05 },
+        frozen: vec![1],
+    };
+    let mut model = model::Linear { m: 0.0, c: 0.5 };
+    learn_history(&teacher,
+                  &cost::LeastSquares {},
+                  &mut model,
+                  history.iter().cycle().take(300).cloned());
+
+    assert_eq!(0.5, model.c);
+    assert!(model.m > 1.0);
+}
+
+#[test]
+#[should_panic(expected = "frozen coefficent 2 out of range")]
+fn freeze_out_of_range() {
+
+    use vikos::Teacher;
+
+    let teacher = teacher::FreezeCoefficents {
+        teacher: teacher::GradientDescent { learning_rate: 0.05 },
+        frozen: vec![2],
+    };
+    teacher.new_training(&model::Linear { m: 0.0, c: 0.0 });
+}
+
+#[test]
+fn bold_driver_accelerates() {
+
+    use vikos::learn_epochs;
+
+    let history = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+    let cost = cost::LeastSquares {};
+
+    let mut model = model::Linear { m: 0.0, c: 0.0 };
+    let teacher = teacher::BoldDriver {
+        learning_rate: 1e-4,
+        increase: 1.05,
+        decrease: 0.5,
+        min_learning_rate: 1e-6,
+        max_learning_rate: 1.0,
+    };
+    let report = learn_epochs(&teacher, &cost, &mut model, &history, 10000, 1e-10);
+    assert!(report.converged);
+    assert!((model.m - 2.0).abs() < 1e-3);
+    // The learning rate grew far beyond its initial value
+    assert!(report.learning_rates.iter().cloned().fold(0.0, f64::max) > 1e-2);
+
+    let mut fixed = model::Linear { m: 0.0, c: 0.0 };
+    let fixed_teacher = teacher::BatchGradientDescent {
+        learning_rate: 1e-4,
+        reduction: training::Reduction::Mean,
+    };
+    let fixed_report = learn_epochs(&fixed_teacher, &cost, &mut fixed, &history, 10000, 1e-10);
+    assert!(fixed_report.epochs_run > 10 * report.epochs_run);
+}
+
+#[test]
+fn tweedie_poisson_fits_mean_count() {
+
+    use vikos::{learn_history, Model};
+
+    // Counts of two groups, with means of 2 and 6
+    let history = [(0.0, 1.0), (0.0, 3.0), (0.0, 0.0), (0.0, 4.0), (1.0, 5.0), (1.0, 9.0),
+                   (1.0, 4.0), (1.0, 6.0)];
+
+    let mut model = model::Exponential::default();
+    learn_history(&teacher::GradientDescent { learning_rate: 0.001 },
+                  &cost::Tweedie { power: 1.0 },
+                  &mut model,
+                  history.iter().cycle().take(20000).cloned());
+
+    assert!((model.predict(&0.0) - 2.0).abs() < 0.05);
+    assert!((model.predict(&1.0) - 6.0).abs() < 0.05);
+}
+
+#[test]
+fn feature_scales() {
+
+    use vikos::{learn_history, Model};
+
+    let model = model::Linear {
+            m: [1.5, -1.0],
+            c: 0.5,
+        }
+        .with_feature_scales([2.0, 1.0]);
+    // Contribution of the first feature is doubled, the second one is unchanged
+    assert_eq!(2.0 * 1.5 * 3.0 - 1.0 * 4.0 + 0.5, model.predict(&[3.0, 4.0]));
+
+    let history = [([0.0, 7.0], 17.0), ([1.0, 2.0], 8.0), ([2.0, -2.0], 1.0)];
+    let teacher = teacher::Momentum {
+        l0: 0.009,
+        t: 1000.0,
+        inertia: 0.995,
+    };
+    let fit = |scales| {
+        let mut model = model::Linear {
+                m: [0.0, 0.0],
+                c: 0.0,
+            }
+            .with_feature_scales(scales);
+        learn_history(&teacher,
+                      &cost::LeastSquares {},
+                      &mut model,
+                      history.iter().cycle().take(3000).cloned());
+        model
+    };
+    let unscaled = fit([1.0, 1.0]);
+    let scaled = fit([2.0, 1.0]);
+
+    assert!((unscaled.linear.m[0] - 1.0).abs() < 0.01);
+    assert!((scaled.linear.m[0] - 0.5).abs() < 0.01);
+    assert!((scaled.linear.m[1] - unscaled.linear.m[1]).abs() < 0.01);
+}
+
+#[test]
+fn feature_scales_dimension() {
+
+    use vikos::{Error, Model};
+
+    // The fields are public, so `validate` checks the scales again
+    let mut model = model::Linear {
+            m: vec![0.0, 0.0],
+            c: 0.0,
+        }
+        .with_feature_scales(vec![1.0, 2.0]);
+    assert_eq!(Ok(()), model.validate(&vec![1.0, 2.0]));
+
+    model.scales.push(3.0);
+    assert_eq!(Err(Error::DimensionMismatch {
+                   expected: 2,
+                   actual: 3,
+               }),
+               model.validate(&vec![1.0, 2.0]));
+}
+
+#[test]
+#[should_panic(expected = "there must be one scale for each weight")]
+fn feature_scales_missing_scale() {
+    model::Linear {
+            m: vec![0.0, 0.0],
+            c: 0.0,
+        }
+        .with_feature_scales(vec![1.0]);
+}
+
+#[test]
+fn learn_history_missing_truths() {
+
+    use vikos::{learn_history, learn_history_with_missing, MissingPolicy};
+
+    let history = [(0.0, Some(1.0)),
+                   (1.0, None),
+                   (1.0, Some(3.0)),
+                   (2.0, Some(5.0)),
+                   (3.0, None),
+                   (3.0, Some(7.0))];
+    let teacher = teacher::GradientDescent { learning_rate: 0.05 };
+    let cost = cost::LeastSquares {};
+
+    let mut skipped = model::Linear { m: 0.0, c: 0.0 };
+    learn_history_with_missing(&teacher,
+                               &cost,
+                               &mut skipped,
+                               history.iter().cycle().take(600).cloned(),
+                               MissingPolicy::Skip);
+
+    let mut filtered = model::Linear { m: 0.0, c: 0.0 };
+    learn_history(&teacher,
+                  &cost,
+                  &mut filtered,
+                  history.iter()
+                      .cycle()
+                      .take(600)
+                      .filter_map(|&(x, truth)| truth.map(|truth| (x, truth))));
+
+    assert_eq!(filtered, skipped);
+    assert!(!skipped.m.is_nan());
+
+    // Imputing the mean truth pulls the fit towards a flatter line
+    let mut imputed = model::Linear { m: 0.0, c: 0.0 };
+    learn_history_with_missing(&teacher,
+                               &cost,
+                               &mut imputed,
+                               history.iter().cycle().take(600).cloned(),
+                               MissingPolicy::ImputeMean);
+    assert!(imputed.m < skipped.m);
+}
@@ -0,0 +1,34 @@
+//! Predictions of models which are available without the standard library
+//!
+//! Run with `cargo test --no-default-features --features libm --test no_std`
+extern crate vikos;
+
+use vikos::Model;
+use vikos::model::{Constant, Linear, Logistic};
+
+#[test]
+fn constant_predict() {
+
+    let model = Constant::new(4.0);
+    assert_eq!(4.0, model.predict(&()));
+}
+
+#[test]
+fn linear_predict() {
+
+    let model = Linear {
+        m: [1.0, 2.0],
+        c: 3.0,
+    };
+    assert_eq!(8.0, model.predict(&[1.0, 2.0]));
+}
+
+#[test]
+fn logistic_predict() {
+
+    let mut model = Logistic::default();
+    *model.coefficent(0) = 1.0;
+    *model.coefficent(1) = -1.0;
+    assert_eq!(0.5, model.predict(&[2.0, 2.0]));
+    assert!(model.predict(&[0.0, 2.0]) > 0.5);
+}